@@ -0,0 +1,87 @@
+CREATE TABLE IF NOT EXISTS "settings"
+(
+	id bigint generated by default as identity
+		constraint settings_pk
+			primary key,
+	log_channel_id bigint,
+	moderation_channel_id bigint,
+	music_order_channel_id bigint,
+	music_log_channel_id bigint,
+	member_role_id bigint
+);
+CREATE UNIQUE INDEX IF NOT EXISTS settings_id_uindex
+	on settings (id);
+CREATE UNIQUE INDEX IF NOT EXISTS settings_log_channel_id_uindex
+	on settings (log_channel_id);
+CREATE UNIQUE INDEX IF NOT EXISTS settings_moderation_channel_id_uindex
+	on settings (moderation_channel_id);
+CREATE UNIQUE INDEX IF NOT EXISTS settings_music_log_channel_id_uindex
+	on settings (music_log_channel_id);
+CREATE UNIQUE INDEX IF NOT EXISTS settings_music_order_channel_id_uindex
+	on settings (music_order_channel_id);
+CREATE TABLE IF NOT EXISTS "guilds"
+(
+	discord_id bigint not null
+		constraint guilds_pk
+			primary key,
+	settings_id bigint not null
+		constraint guilds_settings_id_fk
+			references settings
+				on delete cascade
+);
+CREATE UNIQUE INDEX IF NOT EXISTS guilds_discord_id_uindex
+	on guilds (discord_id);
+CREATE UNIQUE INDEX IF NOT EXISTS guilds_settings_id_uindex
+	on guilds (settings_id);
+CREATE TABLE IF NOT EXISTS "channels"
+(
+	discord_id bigint not null
+		constraint channels_pk
+			primary key,
+	guild_id bigint
+		constraint channels_guilds_discord_id_fk
+			references guilds
+				on delete cascade
+);
+CREATE UNIQUE INDEX IF NOT EXISTS channels_discord_id_uindex
+	on channels (discord_id);
+ALTER TABLE settings
+	ADD CONSTRAINT settings_channels_discord_id_fk
+		foreign key (log_channel_id) references channels
+			on delete set null;
+ALTER TABLE settings
+	ADD CONSTRAINT settings_channels_discord_id_fk_2
+		foreign key (moderation_channel_id) references channels
+			on delete set null;
+ALTER TABLE settings
+	ADD CONSTRAINT settings_channels_discord_id_fk_3
+		foreign key (music_order_channel_id) references channels
+			on delete set null;
+ALTER TABLE settings
+	ADD CONSTRAINT settings_channels_discord_id_fk_4
+		foreign key (music_log_channel_id) references channels
+			on delete set null;
+CREATE TABLE IF NOT EXISTS "music_bots"
+(
+	guild_id bigint not null
+		references guilds
+			on delete cascade,
+	prefix text not null,
+	on_channel_id bigint
+		references channels
+			on delete set null
+);
+CREATE TABLE IF NOT EXISTS "unregistered_members"
+(
+	discord_id bigint not null,
+	guild_id bigint
+		references guilds
+			on delete cascade
+);
+CREATE TABLE IF NOT EXISTS "roles"
+(
+	discord_id bigint not null,
+	guild_id bigint not null
+		references guilds
+			on delete cascade
+);
@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS "cases"
+(
+	guild_id bigint not null
+		references guilds
+			on delete cascade,
+	number bigint not null,
+	action text not null,
+	target_id bigint not null,
+	moderator_id bigint not null,
+	reason text,
+	created_at bigint not null,
+	constraint cases_pk
+		primary key (guild_id, number)
+);
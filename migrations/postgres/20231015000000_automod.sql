@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS "automod_rules"
+(
+	guild_id bigint not null
+		references guilds
+			on delete cascade,
+	kind text not null,
+	threshold bigint,
+	period bigint,
+	pattern text,
+	action text not null
+);
+CREATE INDEX IF NOT EXISTS automod_rules_guild_id_index
+	on automod_rules (guild_id);
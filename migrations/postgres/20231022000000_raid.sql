@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS "raid_settings"
+(
+	guild_id bigint not null
+		constraint raid_settings_pk
+			primary key
+		references guilds
+			on delete cascade,
+	join_threshold bigint,
+	join_period bigint,
+	min_account_age bigint,
+	quarantine_role_id bigint,
+	lockdown bigint not null default 0
+);
+
+ALTER TABLE unregistered_members ADD COLUMN is_paused bigint not null default 0;
@@ -0,0 +1 @@
+ALTER TABLE settings ADD COLUMN disabled_log_events bigint not null default 0;
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS "log_routes"
+(
+	guild_id bigint not null
+		references guilds
+			on delete cascade,
+	category text not null,
+	channel_id bigint not null,
+	constraint log_routes_pk
+		primary key (guild_id, category)
+);
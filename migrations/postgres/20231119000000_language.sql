@@ -0,0 +1,9 @@
+ALTER TABLE settings ADD COLUMN language text;
+
+CREATE TABLE IF NOT EXISTS "user_languages"
+(
+	user_id bigint not null
+		constraint user_languages_pk
+			primary key,
+	language text not null
+);
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS "music_queues"
+(
+	guild_id bigint not null
+		references guilds
+			on delete cascade,
+	prefix text not null,
+	position bigint not null,
+	url text not null,
+	constraint music_queues_pk
+		primary key (guild_id, prefix, position)
+);
@@ -0,0 +1 @@
+ALTER TABLE jobs ADD COLUMN attempts bigint not null default 0;
@@ -0,0 +1 @@
+ALTER TABLE unregistered_members ADD COLUMN is_quarantined bigint not null default 0;
@@ -0,0 +1,83 @@
+CREATE TABLE IF NOT EXISTS "music_bots"
+(
+	guild_id integer not null
+		references guilds
+			on delete cascade,
+	prefix text not null,
+	on_channel_id integer
+		references channels
+			on delete set null
+);
+CREATE TABLE IF NOT EXISTS "guilds"
+(
+	discord_id integer not null
+		constraint guilds_pk
+			primary key,
+	settings_id integer not null
+		constraint guilds_settings_id_fk
+			references settings
+				on delete cascade
+);
+CREATE UNIQUE INDEX IF NOT EXISTS guilds_discord_id_uindex
+	on guilds (discord_id);
+CREATE UNIQUE INDEX IF NOT EXISTS guilds_settings_id_uindex
+	on guilds (settings_id);
+CREATE TABLE IF NOT EXISTS "settings"
+(
+	id integer not null
+		constraint settings_pk
+			primary key autoincrement,
+	log_channel_id integer
+		constraint settings_channels_discord_id_fk
+			references channels
+				on delete set null,
+	moderation_channel_id integer
+		constraint settings_channels_discord_id_fk_2
+			references channels
+				on delete set null,
+	music_order_channel_id integer
+		constraint settings_channels_discord_id_fk_3
+			references channels
+				on delete set null,
+	music_log_channel_id integer
+		constraint settings_channels_discord_id_fk_4
+			references channels
+				on delete set null,
+	member_role_id integer
+);
+CREATE UNIQUE INDEX IF NOT EXISTS settings_id_uindex
+	on settings (id);
+CREATE UNIQUE INDEX IF NOT EXISTS settings_log_channel_id_uindex
+	on settings (log_channel_id);
+CREATE UNIQUE INDEX IF NOT EXISTS settings_moderation_channel_id_uindex
+	on settings (moderation_channel_id);
+CREATE UNIQUE INDEX IF NOT EXISTS settings_music_log_channel_id_uindex
+	on settings (music_log_channel_id);
+CREATE UNIQUE INDEX IF NOT EXISTS settings_music_order_channel_id_uindex
+	on settings (music_order_channel_id);
+CREATE TABLE IF NOT EXISTS "channels"
+(
+	discord_id integer not null
+		constraint channels_pk
+			primary key,
+	guild_id integer
+		constraint channels_guilds_discord_id_fk
+			references guilds
+				on delete cascade
+);
+CREATE UNIQUE INDEX IF NOT EXISTS channels_discord_id_uindex
+	on channels (discord_id);
+CREATE TABLE IF NOT EXISTS "unregistered_members"
+(
+	discord_id integer not null,
+	guild_id integer
+		references guilds
+			on delete cascade
+);
+CREATE TABLE IF NOT EXISTS "roles"
+(
+	discord_id integer not null,
+	guild_id integer not null
+		references guilds
+			on delete cascade
+);
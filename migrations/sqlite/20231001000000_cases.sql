@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS "cases"
+(
+	guild_id integer not null
+		references guilds
+			on delete cascade,
+	number integer not null,
+	action text not null,
+	target_id integer not null,
+	moderator_id integer not null,
+	reason text,
+	created_at integer not null,
+	constraint cases_pk
+		primary key (guild_id, number)
+);
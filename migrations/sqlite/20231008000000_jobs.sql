@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS "jobs"
+(
+	id integer not null
+		constraint jobs_pk
+			primary key autoincrement,
+	due_at integer not null,
+	kind text not null,
+	payload text not null
+);
+CREATE INDEX IF NOT EXISTS jobs_due_at_index
+	on jobs (due_at);
+
+ALTER TABLE cases ADD COLUMN expires_at integer;
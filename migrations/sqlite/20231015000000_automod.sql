@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS "automod_rules"
+(
+	guild_id integer not null
+		references guilds
+			on delete cascade,
+	kind text not null,
+	threshold integer,
+	period integer,
+	pattern text,
+	action text not null
+);
+CREATE INDEX IF NOT EXISTS automod_rules_guild_id_index
+	on automod_rules (guild_id);
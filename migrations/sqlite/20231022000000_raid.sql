@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS "raid_settings"
+(
+	guild_id integer not null
+		constraint raid_settings_pk
+			primary key
+		references guilds
+			on delete cascade,
+	join_threshold integer,
+	join_period integer,
+	min_account_age integer,
+	quarantine_role_id integer,
+	lockdown integer not null default 0
+);
+
+ALTER TABLE unregistered_members ADD COLUMN is_paused integer not null default 0;
@@ -0,0 +1 @@
+ALTER TABLE settings ADD COLUMN disabled_log_events integer not null default 0;
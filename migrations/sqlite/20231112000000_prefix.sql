@@ -0,0 +1,12 @@
+ALTER TABLE settings ADD COLUMN prefix text;
+
+CREATE TABLE IF NOT EXISTS "command_aliases"
+(
+	guild_id integer not null
+		references guilds
+			on delete cascade,
+	alias text not null,
+	command text not null,
+	constraint command_aliases_pk
+		primary key (guild_id, alias)
+);
@@ -0,0 +1 @@
+ALTER TABLE jobs ADD COLUMN attempts integer not null default 0;
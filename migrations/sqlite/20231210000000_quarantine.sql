@@ -0,0 +1 @@
+ALTER TABLE unregistered_members ADD COLUMN is_quarantined integer not null default 0;
@@ -2,73 +2,220 @@
 //!
 
 // submodules
+pub mod audit;
+pub mod automod;
+pub mod backup;
+pub mod cooldown;
 pub mod events;
+pub mod help;
+pub mod intents;
+pub mod locale;
+pub mod moderation;
 pub mod music_order;
 pub mod music_playing;
+pub mod owner;
+pub mod prefix;
+pub mod raid;
+pub mod reply;
+pub mod scheduler;
 pub mod settings;
+pub mod shutdown;
+pub mod supervisor;
 
+use intents::BotRole;
+use locale::LANGUAGE_COMMAND;
 use serenity::{
-    client::{Client, ClientBuilder},
+    cache::Cache,
+    client::{bridge::gateway::ShardManager, Client, ClientBuilder, Context},
     framework::standard::{
-        macros::group,
+        macros::{group, hook},
         {CommandGroup, StandardFramework},
     },
-    model::{gateway::GatewayIntents, id::UserId},
-    prelude::EventHandler,
+    model::{channel::Message, gateway::GatewayIntents, id::UserId},
+    prelude::{EventHandler, Mutex},
     Result,
 };
-use songbird::SerenityInit;
+use songbird::{SerenityInit, Songbird};
+use std::sync::{self, Arc};
 
 /// `General` struct is a global group for `serenity` framework.
 ///
 #[group]
+#[commands(language)]
 pub struct General;
 
+/// Hook that rejects commands once shutdown was started
+/// or if cooldown of user or rate limit of guild does not allow them yet.
+///
+#[hook]
+async fn before(ctx: &Context, message: &Message, command_name: &str) -> bool {
+    !shutdown::is_shutting_down() && cooldown::check(ctx, message, command_name).await
+}
+
 /// `Bot` struct represents discord bot.
 ///
 pub struct Bot {
-    /// Underlying client.
+    /// Function that builds new client of bot, so that it can be restarted after crash.
+    ///
+    builder: Box<dyn Fn() -> ClientBuilder + Send + Sync>,
+    /// Name of bot that is used in logs and statuses.
+    ///
+    name: &'static str,
+    /// Manager of shards of currently running client.
+    ///
+    shard_manager: Arc<sync::Mutex<Option<Arc<Mutex<ShardManager>>>>>,
+    /// Cache of currently running client.
+    ///
+    cache: Arc<sync::Mutex<Option<Arc<Cache>>>>,
+    /// Token of bot.
+    ///
+    token: String,
+    /// Gateway intents that bot connects with.
+    ///
+    intents: GatewayIntents,
+    /// Voice manager of music bot, `None` for other bots.
     ///
-    client: Client,
+    songbird: Option<Arc<Songbird>>,
+    /// Prefix that bot was created with.
+    ///
+    prefix: String,
 }
 impl Bot {
     /// Array of bot developers (owners).
     ///
     pub const OWNERS: [UserId; 1] = [UserId(290146364346597376)];
 
-    /// Initializes new bot with given token.
+    /// Initializes new bot with given name and token.
+    ///
+    /// Music bots always use given prefix, while for other bots it is only a default one
+    /// and guilds are able to set their own prefix and command aliases.
     ///
-    pub async fn new(
+    pub fn new(
+        name: &'static str,
         token: String,
         prefix: &str,
         groups: Vec<&'static CommandGroup>,
         event_handler: impl EventHandler + 'static,
         is_music: bool,
     ) -> Self {
-        let mut framework: StandardFramework =
-            StandardFramework::new().configure(|c| c.prefix(prefix).owners(Self::OWNERS.into()));
-        for group in groups {
+        let mut framework: StandardFramework = StandardFramework::new()
+            .configure(|c| {
+                if is_music {
+                    c.prefix(prefix)
+                } else {
+                    c.prefix("").dynamic_prefix(prefix::dynamic_prefix)
+                }
+                .owners(Self::OWNERS.into())
+            })
+            .before(before)
+            .after(reply::after)
+            .unrecognised_command(prefix::unrecognised_command);
+        for group in groups.iter().copied() {
             framework = framework.group(group);
         }
+        if !is_music {
+            framework = framework
+                .help(&help::HELP)
+                .on_dispatch_error(reply::dispatch_error);
+        }
+        let framework: Arc<StandardFramework> = Arc::new(framework);
+        if !is_music {
+            prefix::init(
+                prefix,
+                Arc::clone(&framework),
+                &groups,
+                help::HELP.options.names,
+            );
+        }
 
-        let intents: GatewayIntents = GatewayIntents::all();
+        let role: BotRole = if is_music {
+            BotRole::Music
+        } else {
+            BotRole::Main
+        };
+        let intents: GatewayIntents = role.intents();
 
-        let client: Client = {
-            let mut client: ClientBuilder = Client::builder(token, intents)
-                .event_handler(event_handler)
-                .framework(framework);
-            if is_music {
-                client = client.register_songbird();
+        let songbird: Option<Arc<Songbird>> = is_music.then(Songbird::serenity);
+        let event_handler: Arc<_> = Arc::new(event_handler);
+        let voice: Option<Arc<Songbird>> = songbird.clone();
+        let client_token: String = token.clone();
+        let builder = move || {
+            let client: ClientBuilder = Client::builder(&client_token, intents)
+                .event_handler_arc(Arc::clone(&event_handler))
+                .framework_arc(Arc::clone(&framework));
+            match &voice {
+                Some(voice) => client.register_songbird_with(Arc::clone(voice)),
+                None => client,
             }
-            client.await.expect("Client should be created correctly")
         };
 
-        Bot { client }
+        Bot {
+            builder: Box::new(builder),
+            name,
+            shard_manager: Arc::new(sync::Mutex::new(None)),
+            cache: Arc::new(sync::Mutex::new(None)),
+            token,
+            intents,
+            songbird,
+            prefix: prefix.to_string(),
+        }
     }
 
-    /// Runs bot.
+    /// Returns name of bot.
     ///
-    pub async fn run(&mut self) -> Result<()> {
-        self.client.start().await
+    pub fn name(&self) -> &'static str {
+        self.name
     }
+
+    /// Returns handle that is used to inspect bot and shut it down.
+    ///
+    pub fn handle(&self) -> BotHandle {
+        BotHandle {
+            name: self.name,
+            shard_manager: Arc::clone(&self.shard_manager),
+            cache: Arc::clone(&self.cache),
+            songbird: self.songbird.clone(),
+            prefix: self.prefix.clone(),
+        }
+    }
+
+    /// Checks that privileged intents that bot needs are enabled for its application.
+    ///
+    pub async fn check_intents(&self) -> bool {
+        intents::check(self.name, &self.token, self.intents).await
+    }
+
+    /// Runs bot with newly built client until it stops.
+    ///
+    pub async fn run(&self) -> Result<()> {
+        let mut client: Client = (self.builder)().await?;
+        *self
+            .shard_manager
+            .lock()
+            .expect("Lock should not be poisoned") = Some(Arc::clone(&client.shard_manager));
+        *self.cache.lock().expect("Lock should not be poisoned") =
+            Some(Arc::clone(&client.cache_and_http.cache));
+        client.start().await
+    }
+}
+
+/// `BotHandle` struct holds parts of running bot that are needed to inspect it and shut it down.
+///
+#[derive(Clone)]
+pub struct BotHandle {
+    /// Name of bot.
+    ///
+    pub name: &'static str,
+    /// Manager of shards of currently running client (`None` if bot was not started yet).
+    ///
+    pub shard_manager: Arc<sync::Mutex<Option<Arc<Mutex<ShardManager>>>>>,
+    /// Cache of currently running client (`None` if bot was not started yet).
+    ///
+    pub cache: Arc<sync::Mutex<Option<Arc<Cache>>>>,
+    /// Voice manager of music bot, `None` for other bots.
+    ///
+    pub songbird: Option<Arc<Songbird>>,
+    /// Prefix that bot was created with.
+    ///
+    pub prefix: String,
 }
@@ -0,0 +1,498 @@
+//! `audit` module implements audit logging of guild events into log channel.
+//!
+
+use crate::{
+    db::{cache, models::LogCategory},
+    logger,
+};
+use serenity::{
+    builder::CreateEmbed,
+    client::Context,
+    model::{
+        channel::{GuildChannel, Message},
+        event::MessageUpdateEvent,
+        guild::{Member, Role},
+        id::{ChannelId, GuildId, MessageId, RoleId, UserId},
+        user::User,
+        voice::VoiceState,
+        Timestamp,
+    },
+    utils::Colour,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    str::FromStr,
+    sync::{Mutex, OnceLock},
+};
+
+/// Number of messages which content is remembered to log edits and deletions.
+///
+const MESSAGE_CACHE_SIZE: usize = 10_000;
+
+/// `AuditEvent` enum represents kind of guild event that can be logged.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AuditEvent {
+    /// Message was edited.
+    ///
+    MessageEdit,
+    /// Message was deleted.
+    ///
+    MessageDelete,
+    /// Member joined guild.
+    ///
+    MemberJoin,
+    /// Member left guild.
+    ///
+    MemberLeave,
+    /// Nickname or roles of member were changed.
+    ///
+    MemberUpdate,
+    /// Channel was created or deleted.
+    ///
+    ChannelChange,
+    /// Role was created or deleted.
+    ///
+    RoleChange,
+    /// Member joined, left or moved between voice channels.
+    ///
+    VoiceMove,
+}
+impl AuditEvent {
+    /// All audit events.
+    ///
+    pub const ALL: [AuditEvent; 8] = [
+        AuditEvent::MessageEdit,
+        AuditEvent::MessageDelete,
+        AuditEvent::MemberJoin,
+        AuditEvent::MemberLeave,
+        AuditEvent::MemberUpdate,
+        AuditEvent::ChannelChange,
+        AuditEvent::RoleChange,
+        AuditEvent::VoiceMove,
+    ];
+
+    /// Returns name of event that is used in commands.
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEvent::MessageEdit => "message_edit",
+            AuditEvent::MessageDelete => "message_delete",
+            AuditEvent::MemberJoin => "member_join",
+            AuditEvent::MemberLeave => "member_leave",
+            AuditEvent::MemberUpdate => "member_update",
+            AuditEvent::ChannelChange => "channel_change",
+            AuditEvent::RoleChange => "role_change",
+            AuditEvent::VoiceMove => "voice_move",
+        }
+    }
+
+    /// Returns category of log channel that event is logged in.
+    ///
+    pub fn category(&self) -> LogCategory {
+        match self {
+            AuditEvent::MessageEdit | AuditEvent::MessageDelete => LogCategory::Messages,
+            AuditEvent::MemberJoin | AuditEvent::MemberLeave => LogCategory::Registration,
+            AuditEvent::MemberUpdate | AuditEvent::ChannelChange | AuditEvent::RoleChange => {
+                LogCategory::ServerChanges
+            }
+            AuditEvent::VoiceMove => LogCategory::Voice,
+        }
+    }
+
+    /// Returns bit of event in `disabled_log_events` setting.
+    ///
+    pub fn bit(&self) -> i64 {
+        1 << (*self as i64)
+    }
+}
+impl FromStr for AuditEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AuditEvent::ALL
+            .into_iter()
+            .find(|event| event.as_str() == s)
+            .ok_or_else(|| format!("Unknown log event '{}'", s))
+    }
+}
+
+/// `CachedMessage` struct holds content of recently sent message.
+///
+struct CachedMessage {
+    channel_id: ChannelId,
+    author_id: UserId,
+    content: String,
+}
+
+/// Recently sent guild messages and order in which they were sent.
+///
+static MESSAGES: OnceLock<Mutex<(HashMap<MessageId, CachedMessage>, VecDeque<MessageId>)>> =
+    OnceLock::new();
+
+/// Remembers content of guild message.
+///
+pub fn remember_message(message: &Message) {
+    if message.guild_id.is_none() || message.author.bot {
+        return;
+    }
+
+    let mut messages = MESSAGES
+        .get_or_init(|| Mutex::new((HashMap::new(), VecDeque::new())))
+        .lock()
+        .expect("Lock should not be poisoned");
+    let (cache, order) = &mut *messages;
+    if order.len() >= MESSAGE_CACHE_SIZE {
+        if let Some(oldest) = order.pop_front() {
+            let _ = cache.remove(&oldest);
+        }
+    }
+    let _ = cache.insert(
+        message.id,
+        CachedMessage {
+            channel_id: message.channel_id,
+            author_id: message.author.id,
+            content: message.content.clone(),
+        },
+    );
+    order.push_back(message.id);
+}
+
+/// Returns whether logging of event is enabled on guild.
+///
+async fn is_enabled(guild_id: GuildId, event: AuditEvent) -> bool {
+    let disabled_log_events: i64 = cache::get(guild_id)
+        .await
+        .map(|setting| setting.disabled_log_events)
+        .unwrap_or_default();
+    disabled_log_events & event.bit() == 0
+}
+
+/// Logs event as embed if it is enabled on guild.
+///
+async fn log_event(
+    ctx: &Context,
+    guild_id: GuildId,
+    event: AuditEvent,
+    colour: Colour,
+    title: &str,
+    description: String,
+    fields: Vec<(&str, String)>,
+) {
+    if !is_enabled(guild_id, event).await {
+        return;
+    }
+
+    let mut embed: CreateEmbed = CreateEmbed::default();
+    let _ = embed
+        .title(title)
+        .description(&description)
+        .colour(colour)
+        .timestamp(Timestamp::now());
+    for (name, value) in fields {
+        if !value.is_empty() {
+            let _ = embed.field(name, truncate(&value), false);
+        }
+    }
+    logger::log_discord_embed(
+        &ctx.http,
+        guild_id,
+        event.category(),
+        &format!("{}: {}", title, description),
+        embed,
+    );
+}
+
+/// Truncates text to fit into embed field.
+///
+fn truncate(text: &str) -> String {
+    const MAX_LENGTH: usize = 1024;
+    if text.chars().count() <= MAX_LENGTH {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(MAX_LENGTH - 1).collect::<String>();
+    truncated.push('…');
+    truncated
+}
+
+/// Logs message edit.
+///
+pub async fn message_edit(ctx: &Context, event: &MessageUpdateEvent) {
+    let (guild_id, new_content) = match (event.guild_id, &event.content) {
+        (Some(guild_id), Some(new_content)) => (guild_id, new_content.clone()),
+        _ => return,
+    };
+    let (author_id, old_content): (Option<UserId>, Option<String>) = {
+        let mut messages = MESSAGES
+            .get_or_init(|| Mutex::new((HashMap::new(), VecDeque::new())))
+            .lock()
+            .expect("Lock should not be poisoned");
+        match messages.0.get_mut(&event.id) {
+            Some(cached) => (
+                Some(cached.author_id),
+                Some(std::mem::replace(&mut cached.content, new_content.clone())),
+            ),
+            None => (event.author.as_ref().map(|author| author.id), None),
+        }
+    };
+    if old_content.as_deref() == Some(new_content.as_str()) {
+        return;
+    }
+
+    log_event(
+        ctx,
+        guild_id,
+        AuditEvent::MessageEdit,
+        Colour::ORANGE,
+        "Message edited",
+        format!(
+            "Message by {} was edited in <#{}>",
+            author_id.map_or(String::from("unknown user"), |id| format!("<@{}>", id)),
+            event.channel_id
+        ),
+        vec![
+            (
+                "Before",
+                old_content.unwrap_or_else(|| String::from("*not cached*")),
+            ),
+            ("After", new_content),
+        ],
+    )
+    .await;
+}
+
+/// Logs message deletion.
+///
+pub async fn message_delete(
+    ctx: &Context,
+    guild_id: Option<GuildId>,
+    channel_id: ChannelId,
+    message_id: MessageId,
+) {
+    let guild_id: GuildId = match guild_id {
+        Some(guild_id) => guild_id,
+        None => return,
+    };
+    let cached: Option<CachedMessage> = {
+        let mut messages = MESSAGES
+            .get_or_init(|| Mutex::new((HashMap::new(), VecDeque::new())))
+            .lock()
+            .expect("Lock should not be poisoned");
+        let (cache, order) = &mut *messages;
+        let cached: Option<CachedMessage> = cache.remove(&message_id);
+        if cached.is_some() {
+            order.retain(|id| *id != message_id);
+        }
+        cached
+    };
+
+    let (description, content): (String, String) = match cached {
+        Some(cached) => (
+            format!(
+                "Message by <@{}> was deleted in <#{}>",
+                cached.author_id, cached.channel_id
+            ),
+            cached.content,
+        ),
+        None => (
+            format!("Message was deleted in <#{}>", channel_id),
+            String::from("*not cached*"),
+        ),
+    };
+    log_event(
+        ctx,
+        guild_id,
+        AuditEvent::MessageDelete,
+        Colour::RED,
+        "Message deleted",
+        description,
+        vec![("Content", content)],
+    )
+    .await;
+}
+
+/// Logs member join.
+///
+pub async fn member_join(ctx: &Context, member: &Member) {
+    log_event(
+        ctx,
+        member.guild_id,
+        AuditEvent::MemberJoin,
+        Colour::DARK_GREEN,
+        "Member joined",
+        format!(
+            "<@{}> ({}) joined the server",
+            member.user.id,
+            member.user.tag()
+        ),
+        vec![(
+            "Account created",
+            format!("<t:{}:R>", member.user.id.created_at().unix_timestamp()),
+        )],
+    )
+    .await;
+}
+
+/// Logs member leave.
+///
+pub async fn member_leave(ctx: &Context, guild_id: GuildId, user: &User) {
+    log_event(
+        ctx,
+        guild_id,
+        AuditEvent::MemberLeave,
+        Colour::DARK_RED,
+        "Member left",
+        format!("<@{}> ({}) left the server", user.id, user.tag()),
+        vec![],
+    )
+    .await;
+}
+
+/// Logs nickname and role changes of member.
+///
+pub async fn member_update(ctx: &Context, old: Option<&Member>, new: &Member) {
+    let old: &Member = match old {
+        Some(old) => old,
+        None => return,
+    };
+
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    if old.nick != new.nick {
+        fields.push((
+            "Nickname",
+            format!(
+                "{} → {}",
+                old.nick.as_deref().unwrap_or("*none*"),
+                new.nick.as_deref().unwrap_or("*none*")
+            ),
+        ));
+    }
+    let mention_roles = |roles: Vec<&RoleId>| -> String {
+        roles
+            .into_iter()
+            .map(|role_id| format!("<@&{}>", role_id))
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+    let added_roles: String = mention_roles(
+        new.roles
+            .iter()
+            .filter(|role_id| !old.roles.contains(*role_id))
+            .collect(),
+    );
+    let removed_roles: String = mention_roles(
+        old.roles
+            .iter()
+            .filter(|role_id| !new.roles.contains(*role_id))
+            .collect(),
+    );
+    fields.push(("Added roles", added_roles));
+    fields.push(("Removed roles", removed_roles));
+    if fields.iter().all(|(_, value)| value.is_empty()) {
+        return;
+    }
+
+    log_event(
+        ctx,
+        new.guild_id,
+        AuditEvent::MemberUpdate,
+        Colour::BLUE,
+        "Member updated",
+        format!("<@{}> was updated", new.user.id),
+        fields,
+    )
+    .await;
+}
+
+/// Logs channel creation or deletion.
+///
+pub async fn channel_change(ctx: &Context, channel: &GuildChannel, is_created: bool) {
+    log_event(
+        ctx,
+        channel.guild_id,
+        AuditEvent::ChannelChange,
+        if is_created {
+            Colour::DARK_GREEN
+        } else {
+            Colour::DARK_RED
+        },
+        if is_created {
+            "Channel created"
+        } else {
+            "Channel deleted"
+        },
+        format!("'{}' ({:?}) channel", channel.name, channel.kind),
+        vec![],
+    )
+    .await;
+}
+
+/// Logs role creation.
+///
+pub async fn role_create(ctx: &Context, role: &Role) {
+    log_event(
+        ctx,
+        role.guild_id,
+        AuditEvent::RoleChange,
+        Colour::DARK_GREEN,
+        "Role created",
+        format!("<@&{}> ('{}') role", role.id, role.name),
+        vec![],
+    )
+    .await;
+}
+
+/// Logs role deletion.
+///
+pub async fn role_delete(ctx: &Context, guild_id: GuildId, role_id: RoleId, role: Option<&Role>) {
+    log_event(
+        ctx,
+        guild_id,
+        AuditEvent::RoleChange,
+        Colour::DARK_RED,
+        "Role deleted",
+        match role {
+            Some(role) => format!("'{}' role", role.name),
+            None => format!("Role with {} id", role_id),
+        },
+        vec![],
+    )
+    .await;
+}
+
+/// Logs joins, leaves and moves between voice channels.
+///
+pub async fn voice_move(ctx: &Context, old: Option<&VoiceState>, new: &VoiceState) {
+    let guild_id: GuildId = match new.guild_id {
+        Some(guild_id) => guild_id,
+        None => return,
+    };
+    let old_channel_id: Option<ChannelId> = old.and_then(|old| old.channel_id);
+    if old_channel_id == new.channel_id {
+        return;
+    }
+
+    let description: String = match (old_channel_id, new.channel_id) {
+        (None, Some(new_channel_id)) => {
+            format!("<@{}> joined <#{}>", new.user_id, new_channel_id)
+        }
+        (Some(old_channel_id), None) => {
+            format!("<@{}> left <#{}>", new.user_id, old_channel_id)
+        }
+        (Some(old_channel_id), Some(new_channel_id)) => format!(
+            "<@{}> moved from <#{}> to <#{}>",
+            new.user_id, old_channel_id, new_channel_id
+        ),
+        (None, None) => return,
+    };
+    log_event(
+        ctx,
+        guild_id,
+        AuditEvent::VoiceMove,
+        Colour::PURPLE,
+        "Voice state changed",
+        description,
+        vec![],
+    )
+    .await;
+}
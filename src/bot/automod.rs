@@ -0,0 +1,644 @@
+//! `automod` module implements automatic moderation of guild messages
+//! and commands that are used to configure its rules.
+//!
+
+use crate::{
+    bot::{
+        locale::{self, tr},
+        moderation::open_case,
+        reply,
+    },
+    db::{
+        connections::SERVERS_DB,
+        models::{
+            AutomodAction, AutomodRule, AutomodRuleKind, CaseAction, DbId, Language, LogCategory,
+        },
+    },
+    logger, metrics,
+};
+use regex::Regex;
+use serenity::{
+    client::Context,
+    framework::standard::{
+        macros::{command, group},
+        {Args, CommandError, CommandResult},
+    },
+    model::{
+        channel::Message,
+        id::{GuildId, UserId},
+        Timestamp,
+    },
+};
+use sqlx::{Any, AnyPool, Transaction};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+/// `Automod` struct is a group for `serenity` framework that contains automod configuration commands.
+///
+#[group]
+#[prefixes("automod")]
+#[required_permissions("ADMINISTRATOR")]
+#[only_in(guilds)]
+#[default_command(rules)]
+#[commands(rate, duplicates, mentions, invites, word, remove, rules)]
+pub struct Automod;
+
+/// Duration of timeout that is given for automod violation.
+///
+const AUTOMOD_TIMEOUT: i64 = 10 * 60;
+
+/// How long messages are kept in history for rate and duplicates rules.
+///
+const MAX_PERIOD: Duration = Duration::from_secs(10 * 60);
+
+/// Number of tracked members after which whole history is cleaned up.
+///
+const HISTORY_CLEANUP_THRESHOLD: usize = 10_000;
+
+/// Maximal length of discord message.
+///
+const MAX_MESSAGE_LENGTH: usize = 2000;
+
+/// Cached automod rules of guilds.
+///
+static RULES: OnceLock<Mutex<HashMap<GuildId, Arc<Vec<AutomodRule>>>>> = OnceLock::new();
+
+/// Recently sent messages of every member.
+///
+static HISTORY: OnceLock<Mutex<HashMap<(GuildId, UserId), Vec<(Instant, String)>>>> =
+    OnceLock::new();
+
+/// Compiled regular expressions of `words` rules of cached guilds.
+///
+static PATTERNS: OnceLock<Mutex<HashMap<String, Option<Regex>>>> = OnceLock::new();
+
+/// Regular expression that matches discord invite links.
+///
+static INVITE: OnceLock<Regex> = OnceLock::new();
+
+/// Returns whether message violates given rule.
+///
+fn is_violated(rule: &AutomodRule, message: &Message, history: &[(Instant, String)]) -> bool {
+    let threshold: usize = rule.threshold.unwrap_or(0).max(0) as usize;
+    let period: Duration = Duration::from_secs(rule.period.unwrap_or(0).max(0) as u64);
+    match rule.kind {
+        AutomodRuleKind::Rate => {
+            history
+                .iter()
+                .filter(|(sent_at, _)| sent_at.elapsed() <= period)
+                .count()
+                > threshold
+        }
+        AutomodRuleKind::Duplicates => {
+            history
+                .iter()
+                .filter(|(sent_at, content)| {
+                    sent_at.elapsed() <= period && *content == message.content
+                })
+                .count()
+                > threshold
+        }
+        AutomodRuleKind::Mentions => {
+            message.mentions.len()
+                + message.mention_roles.len()
+                + usize::from(message.mention_everyone)
+                > threshold
+        }
+        AutomodRuleKind::Invites => INVITE
+            .get_or_init(|| {
+                Regex::new(r"(?i)(discord\.gg|discord(app)?\.com/invite)/[a-z0-9-]+")
+                    .expect("Regex should be correct")
+            })
+            .is_match(&message.content),
+        AutomodRuleKind::Words => {
+            let pattern: &str = match &rule.pattern {
+                Some(pattern) => pattern,
+                None => return false,
+            };
+            let mut patterns = PATTERNS
+                .get_or_init(|| Mutex::new(HashMap::new()))
+                .lock()
+                .expect("Lock should not be poisoned");
+            patterns
+                .entry(pattern.to_string())
+                .or_insert_with(|| Regex::new(pattern).ok())
+                .as_ref()
+                .is_some_and(|regex| regex.is_match(&message.content))
+        }
+    }
+}
+
+/// Executes action of violated rule.
+///
+async fn punish(ctx: &Context, message: &Message, guild_id: GuildId, rule: &AutomodRule) {
+    if let Err(error) = message.delete(ctx).await {
+        logger::log(
+            log::Level::Warn,
+            &format!("Automod could not delete message: {}", error),
+        );
+    }
+
+    let reason: String = format!("Automod: '{}' rule was violated", rule.kind.as_str());
+    match rule.action {
+        AutomodAction::Delete => {}
+        AutomodAction::Warn => {
+            if let Err(error) = open_case(
+                &ctx.http,
+                guild_id,
+                CaseAction::Warn,
+                message.author.id.into(),
+                ctx.cache.current_user_id().into(),
+                Some(&reason),
+                None,
+            )
+            .await
+            {
+                logger::log(
+                    log::Level::Warn,
+                    &format!("Automod could not open case: {}", error),
+                );
+            }
+        }
+        AutomodAction::Timeout => {
+            let until: Timestamp = match Timestamp::from_unix_timestamp(
+                Timestamp::now().unix_timestamp() + AUTOMOD_TIMEOUT,
+            ) {
+                Ok(until) => until,
+                Err(_) => return,
+            };
+            if let Err(error) = guild_id
+                .edit_member(&ctx.http, message.author.id, |m| {
+                    m.disable_communication_until_datetime(until)
+                })
+                .await
+            {
+                logger::log(
+                    log::Level::Warn,
+                    &format!("Automod could not timeout member: {}", error),
+                );
+                return;
+            }
+            if let Err(error) = open_case(
+                &ctx.http,
+                guild_id,
+                CaseAction::Timeout,
+                message.author.id.into(),
+                ctx.cache.current_user_id().into(),
+                Some(&reason),
+                Some(until.unix_timestamp()),
+            )
+            .await
+            {
+                logger::log(
+                    log::Level::Warn,
+                    &format!("Automod could not open case: {}", error),
+                );
+            }
+        }
+    }
+    logger::log_discord(
+        &ctx.http,
+        guild_id,
+        LogCategory::Moderation,
+        &format!(
+            "Automod applied '{}' to '{}' in <#{}> ({})",
+            rule.action.as_str(),
+            message.author.name,
+            message.channel_id,
+            reason
+        ),
+    );
+}
+
+/// Returns automod rules of guild, loading them into cache if they are not there yet.
+///
+async fn get_rules(guild_id: GuildId) -> Arc<Vec<AutomodRule>> {
+    if let Some(rules) = RULES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned")
+        .get(&guild_id)
+    {
+        return rules.clone();
+    }
+
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+    let rules: Arc<Vec<AutomodRule>> = Arc::new(
+        metrics::timed(
+            "automod_rules.get",
+            sqlx::query_as::<_, AutomodRule>("SELECT * FROM automod_rules WHERE guild_id = $1")
+                .bind(guild_id.db_id())
+                .fetch_all(connection),
+        )
+        .await
+        .expect("Query should be correct"),
+    );
+    let _ = RULES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned")
+        .insert(guild_id, rules.clone());
+    rules
+}
+
+/// Removes cached automod rules of guild, so that they are loaded again on next message.
+///
+/// Compiled patterns that are no longer used by any cached rule are dropped as well.
+///
+pub fn invalidate(guild_id: GuildId) {
+    let mut rules = RULES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned");
+    let _ = rules.remove(&guild_id);
+    PATTERNS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned")
+        .retain(|pattern, _| {
+            rules
+                .values()
+                .flat_map(|rules| rules.iter())
+                .any(|rule| rule.pattern.as_deref() == Some(pattern.as_str()))
+        });
+}
+
+/// Checks guild message against automod rules of its guild.
+///
+pub async fn check(ctx: &Context, message: &Message) {
+    let guild_id: GuildId = match message.guild_id {
+        Some(guild_id) => guild_id,
+        None => return,
+    };
+    if message.author.bot {
+        return;
+    }
+
+    let rules: Arc<Vec<AutomodRule>> = get_rules(guild_id).await;
+    if rules.is_empty() {
+        return;
+    }
+    let is_exempt: bool = message
+        .guild(&ctx.cache)
+        .and_then(|guild| {
+            guild
+                .members
+                .get(&message.author.id)
+                .map(|member| guild.member_permissions(member).manage_messages())
+        })
+        .unwrap_or(false);
+    if is_exempt {
+        return;
+    }
+
+    let violated: Option<&AutomodRule> = {
+        let mut history = HISTORY
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .expect("Lock should not be poisoned");
+        if history.len() > HISTORY_CLEANUP_THRESHOLD {
+            history.retain(|_, messages| {
+                messages.retain(|(sent_at, _)| sent_at.elapsed() <= MAX_PERIOD);
+                !messages.is_empty()
+            });
+        }
+
+        let messages: &mut Vec<(Instant, String)> =
+            history.entry((guild_id, message.author.id)).or_default();
+        messages.retain(|(sent_at, _)| sent_at.elapsed() <= MAX_PERIOD);
+        messages.push((Instant::now(), message.content.clone()));
+
+        let violated: Option<&AutomodRule> = rules
+            .iter()
+            .find(|rule| is_violated(rule, message, messages));
+        if violated.is_some() {
+            messages.clear();
+        }
+        violated
+    };
+    if let Some(rule) = violated {
+        punish(ctx, message, guild_id, rule).await;
+    }
+}
+
+/// Replaces rule of given kind with new one.
+///
+async fn set_rule(
+    guild_id: GuildId,
+    kind: AutomodRuleKind,
+    threshold: Option<i64>,
+    period: Option<i64>,
+    action: AutomodAction,
+) {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+    let _timer: metrics::Timer = metrics::Timer::query("automod_rules.set");
+
+    let mut transaction: Transaction<'_, Any> = connection
+        .begin()
+        .await
+        .expect("Transaction should be started");
+    sqlx::query("DELETE FROM automod_rules WHERE guild_id = $1 AND kind = $2")
+        .bind(guild_id.db_id())
+        .bind(kind.to_string())
+        .execute(&mut *transaction)
+        .await
+        .expect("Query should be correct");
+    sqlx::query("INSERT INTO automod_rules VALUES ($1, $2, $3, $4, NULL, $5)")
+        .bind(guild_id.db_id())
+        .bind(kind.to_string())
+        .bind(threshold)
+        .bind(period)
+        .bind(action.to_string())
+        .execute(&mut *transaction)
+        .await
+        .expect("Query should be correct");
+    transaction
+        .commit()
+        .await
+        .expect("Transaction should be committed");
+    invalidate(guild_id);
+}
+
+/// Parses automod action, returning error that is shown to user if it is incorrect.
+///
+fn parse_action(language: Language, action: &str) -> Result<AutomodAction, CommandError> {
+    AutomodAction::from_str(action).map_err(|_| {
+        reply::user_error(tr(
+            language,
+            "automod.unknown_action",
+            &[("action", &action)],
+        ))
+    })
+}
+
+macro_rules! setup_windowed_rule {
+    ($fullname:ident, $kind:expr, $key:literal) => {
+        #[command]
+        #[usage("<messages> <seconds> <action>")]
+        #[example("5 10 timeout")]
+        #[num_args(3)]
+        pub async fn $fullname(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+            let guild_id: GuildId = message
+                .guild_id
+                .expect("This should be called only on guilds");
+            let language: Language =
+                locale::user_language(message.guild_id, message.author.id).await;
+            let threshold: u32 = args.single::<u32>()?;
+            let period: u32 = args.single::<u32>()?;
+            let action: AutomodAction = parse_action(language, &args.single::<String>()?)?;
+            if period == 0 || u64::from(period) > MAX_PERIOD.as_secs() {
+                return Err(reply::user_error(tr(
+                    language,
+                    "automod.wrong_period",
+                    &[("max", &MAX_PERIOD.as_secs())],
+                )));
+            }
+
+            set_rule(
+                guild_id,
+                $kind,
+                Some(threshold.into()),
+                Some(period.into()),
+                action,
+            )
+            .await;
+            reply::success(
+                ctx,
+                message,
+                &tr(
+                    language,
+                    $key,
+                    &[
+                        ("action", &action.as_str()),
+                        ("count", &threshold),
+                        ("seconds", &period),
+                    ],
+                ),
+            )
+            .await?;
+
+            logger::log(
+                log::Level::Info,
+                concat!("automod ", stringify!($fullname), " was called"),
+            );
+            Ok(())
+        }
+    };
+}
+setup_windowed_rule!(rate, AutomodRuleKind::Rate, "automod.rate_set");
+setup_windowed_rule!(
+    duplicates,
+    AutomodRuleKind::Duplicates,
+    "automod.duplicates_set"
+);
+
+#[command]
+#[usage("<mentions> <action>")]
+#[example("5 timeout")]
+#[num_args(2)]
+pub async fn mentions(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let threshold: u32 = args.single::<u32>()?;
+    let action: AutomodAction = parse_action(language, &args.single::<String>()?)?;
+
+    set_rule(
+        guild_id,
+        AutomodRuleKind::Mentions,
+        Some(threshold.into()),
+        None,
+        action,
+    )
+    .await;
+    reply::success(
+        ctx,
+        message,
+        &tr(
+            language,
+            "automod.mentions_set",
+            &[("action", &action.as_str()), ("count", &threshold)],
+        ),
+    )
+    .await?;
+
+    logger::log(log::Level::Info, "automod mentions was called");
+    Ok(())
+}
+
+#[command]
+#[usage("<action>")]
+#[example("delete")]
+#[num_args(1)]
+pub async fn invites(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let action: AutomodAction = parse_action(language, &args.single::<String>()?)?;
+
+    set_rule(guild_id, AutomodRuleKind::Invites, None, None, action).await;
+    reply::success(
+        ctx,
+        message,
+        &tr(
+            language,
+            "automod.invites_set",
+            &[("action", &action.as_str())],
+        ),
+    )
+    .await?;
+
+    logger::log(log::Level::Info, "automod invites was called");
+    Ok(())
+}
+
+#[command]
+#[usage("<pattern> <action>")]
+#[example("\"free\\s+nitro\" delete")]
+#[num_args(2)]
+pub async fn word(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let pattern: String = args.single_quoted::<String>()?;
+    let action: AutomodAction = parse_action(language, &args.single::<String>()?)?;
+    if let Err(error) = Regex::new(&pattern) {
+        return Err(reply::user_error(tr(
+            language,
+            "automod.wrong_pattern",
+            &[("error", &error)],
+        )));
+    }
+
+    metrics::timed(
+        "automod_rules.insert",
+        sqlx::query("INSERT INTO automod_rules VALUES ($1, $2, NULL, NULL, $3, $4)")
+            .bind(guild_id.db_id())
+            .bind(AutomodRuleKind::Words.to_string())
+            .bind(&pattern)
+            .bind(action.to_string())
+            .execute(connection),
+    )
+    .await
+    .expect("Query should be correct");
+    invalidate(guild_id);
+    reply::success(
+        ctx,
+        message,
+        &tr(
+            language,
+            "automod.word_set",
+            &[("action", &action.as_str()), ("pattern", &pattern)],
+        ),
+    )
+    .await?;
+
+    logger::log(log::Level::Info, "automod word was called");
+    Ok(())
+}
+
+#[command]
+#[usage("<rule>")]
+#[example("invites")]
+#[num_args(1)]
+pub async fn remove(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let rule: String = args.single::<String>()?;
+    let kind: AutomodRuleKind = AutomodRuleKind::from_str(&rule)
+        .map_err(|_| reply::user_error(tr(language, "automod.unknown_rule", &[("rule", &rule)])))?;
+
+    metrics::timed(
+        "automod_rules.delete",
+        sqlx::query("DELETE FROM automod_rules WHERE guild_id = $1 AND kind = $2")
+            .bind(guild_id.db_id())
+            .bind(kind.to_string())
+            .execute(connection),
+    )
+    .await
+    .expect("Query should be correct");
+    invalidate(guild_id);
+    reply::success(
+        ctx,
+        message,
+        &tr(language, "automod.removed", &[("rule", &kind.as_str())]),
+    )
+    .await?;
+
+    logger::log(log::Level::Info, "automod remove was called");
+    Ok(())
+}
+
+#[command]
+#[num_args(0)]
+pub async fn rules(ctx: &Context, message: &Message, _: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let rules: Arc<Vec<AutomodRule>> = get_rules(guild_id).await;
+
+    if rules.is_empty() {
+        reply::info(ctx, message, &tr(language, "automod.no_rules", &[])).await?;
+        return Ok(());
+    }
+    let mut reply: String = format!("{}\n", tr(language, "automod.rules", &[]));
+    for rule in rules.iter() {
+        let condition: String = match rule.kind {
+            AutomodRuleKind::Rate | AutomodRuleKind::Duplicates => tr(
+                language,
+                "automod.condition.messages",
+                &[
+                    ("count", &rule.threshold.unwrap_or(0)),
+                    ("seconds", &rule.period.unwrap_or(0)),
+                ],
+            ),
+            AutomodRuleKind::Mentions => tr(
+                language,
+                "automod.condition.mentions",
+                &[("count", &rule.threshold.unwrap_or(0))],
+            ),
+            AutomodRuleKind::Invites => tr(language, "automod.condition.invites", &[]),
+            AutomodRuleKind::Words => tr(
+                language,
+                "automod.condition.words",
+                &[("pattern", &rule.pattern.as_deref().unwrap_or_default())],
+            ),
+        };
+        let line: String = format!(
+            "{} | {} -> {}\n",
+            rule.kind.as_str(),
+            condition,
+            rule.action.as_str()
+        );
+        if reply.len() + line.len() > MAX_MESSAGE_LENGTH {
+            reply::info(ctx, message, &reply).await?;
+            reply.clear();
+        }
+        reply.push_str(&line);
+    }
+    reply::info(ctx, message, &reply).await?;
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+//! `backup` module contains commands that bot owners use to manage database backups.
+//!
+
+use crate::{
+    bot::{
+        locale::{self, tr},
+        reply::{self, ReplyKind},
+    },
+    db::{self, models::Language},
+    logger,
+};
+use serenity::{
+    client::Context,
+    framework::standard::{
+        macros::{command, group},
+        {Args, CommandResult},
+    },
+    model::channel::Message,
+};
+
+/// `Backup` struct is a group for `serenity` framework that contains database backup commands.
+///
+#[group]
+#[prefixes("backup")]
+#[owners_only]
+#[commands(now)]
+pub struct Backup;
+
+#[command]
+#[num_args(0)]
+pub async fn now(ctx: &Context, message: &Message, _: Args) -> CommandResult {
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+
+    let (kind, reply): (ReplyKind, String) = match db::backup::create().await {
+        Ok(path) => (
+            ReplyKind::Success,
+            tr(language, "backup.created", &[("file", &path.display())]),
+        ),
+        Err(error) => (
+            ReplyKind::Error,
+            tr(language, "backup.failed", &[("error", &error)]),
+        ),
+    };
+    reply::send(ctx, message, kind, &reply).await?;
+
+    logger::log(log::Level::Info, "backup now was called");
+    Ok(())
+}
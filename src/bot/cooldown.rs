@@ -0,0 +1,361 @@
+//! `cooldown` module implements command cooldowns of users and command rate limit of guilds.
+//!
+//! Cooldowns are configured with `COMMAND_COOLDOWNS` variable, which contains pairs of command name
+//! and cooldown in seconds separated by commas (e.g. `play=5,join=10`); `*` sets cooldown
+//! of all other commands. Rate limit of guild is configured with `GUILD_COMMAND_RATE` variable
+//! as number of commands per number of seconds (e.g. `20/10`). Owners are not limited.
+//!
+
+use crate::{
+    bot::{
+        locale::{self, tr},
+        reply, Bot,
+    },
+    db::models::Language,
+};
+use serenity::{
+    client::Context,
+    model::{
+        channel::Message,
+        id::{GuildId, UserId},
+    },
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Mutex, MutexGuard, OnceLock},
+    time::{Duration, Instant},
+};
+
+/// Cooldowns that are used if `COMMAND_COOLDOWNS` is not set.
+///
+const DEFAULT_COOLDOWNS: &str = "play=5,join=10,leave=5,pause=2,resume=2,skip=2,stop=5";
+
+/// Rate limit that is used if `GUILD_COMMAND_RATE` is not set.
+///
+const DEFAULT_GUILD_RATE: &str = "20/10";
+
+/// Interval between two consecutive removals of usages whose cooldowns have passed.
+///
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// `Limits` struct represents configured cooldowns and rate limit.
+///
+struct Limits {
+    /// Cooldowns of commands.
+    ///
+    cooldowns: HashMap<String, Duration>,
+    /// Cooldown of commands that are not listed in `cooldowns`.
+    ///
+    default_cooldown: Duration,
+    /// Number of commands that guild is able to use during `guild_period`.
+    ///
+    guild_limit: usize,
+    /// Period of guild rate limit.
+    ///
+    guild_period: Duration,
+}
+impl Limits {
+    /// Returns longest configured cooldown, after which usages are forgotten.
+    ///
+    fn max_cooldown(&self) -> Duration {
+        self.cooldowns
+            .values()
+            .copied()
+            .chain([self.default_cooldown])
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// `Verdict` enum represents decision about command.
+///
+enum Verdict {
+    /// Command can be used.
+    ///
+    Allow,
+    /// Command can be used after given time, which should be told to user.
+    ///
+    Wait(Duration),
+    /// Command can not be used and user was already told about it, so it is ignored silently.
+    ///
+    Ignore,
+}
+
+/// `Usage` struct represents last use of command by user.
+///
+struct Usage {
+    /// Moment when command was used.
+    ///
+    used_at: Instant,
+    /// Whether user was already told about cooldown, so that spam does not produce more replies.
+    ///
+    notified: bool,
+}
+
+/// Configured limits.
+///
+static LIMITS: OnceLock<Limits> = OnceLock::new();
+
+/// Last uses of commands by users.
+///
+static USAGES: OnceLock<Mutex<HashMap<(UserId, String), Usage>>> = OnceLock::new();
+
+/// Moment when usages whose cooldowns have passed were removed last time.
+///
+static PRUNED_AT: OnceLock<Mutex<Instant>> = OnceLock::new();
+
+/// Recent commands of guilds and whether guild was already told about rate limit.
+///
+static GUILD_USAGES: OnceLock<Mutex<HashMap<GuildId, (VecDeque<Instant>, bool)>>> = OnceLock::new();
+
+/// Parses cooldowns from pairs of command name and seconds separated by commas.
+///
+fn parse_cooldowns(cooldowns: &str) -> Result<HashMap<String, Duration>, String> {
+    cooldowns
+        .split(',')
+        .map(str::trim)
+        .filter(|cooldown| !cooldown.is_empty())
+        .map(|cooldown| {
+            let (command, seconds): (&str, &str) = cooldown.split_once('=').ok_or_else(|| {
+                format!("cooldown '{}' should be given as command=seconds", cooldown)
+            })?;
+            let seconds: u64 = seconds
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("cooldown of '{}' should be a number", command.trim()))?;
+            Ok((command.trim().to_string(), Duration::from_secs(seconds)))
+        })
+        .collect::<Result<HashMap<String, Duration>, String>>()
+}
+
+/// Parses rate limit that is given as number of commands per number of seconds.
+///
+fn parse_rate(rate: &str) -> Result<(usize, Duration), String> {
+    let (limit, seconds): (&str, &str) = rate
+        .split_once('/')
+        .ok_or_else(|| format!("rate '{}' should be given as commands/seconds", rate))?;
+    let limit: usize = limit
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| format!("rate '{}' should contain numbers", rate))?;
+    let seconds: u64 = seconds
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| format!("rate '{}' should contain numbers", rate))?;
+    Ok((limit, Duration::from_secs(seconds)))
+}
+
+/// Returns configured limits.
+///
+fn limits() -> &'static Limits {
+    LIMITS.get_or_init(|| {
+        let mut cooldowns: HashMap<String, Duration> = parse_cooldowns(
+            &dotenv::var("COMMAND_COOLDOWNS").unwrap_or(DEFAULT_COOLDOWNS.to_string()),
+        )
+        .expect("COMMAND_COOLDOWNS should be correct");
+        let default_cooldown: Duration = cooldowns.remove("*").unwrap_or_default();
+        let (guild_limit, guild_period): (usize, Duration) = parse_rate(
+            &dotenv::var("GUILD_COMMAND_RATE").unwrap_or(DEFAULT_GUILD_RATE.to_string()),
+        )
+        .expect("GUILD_COMMAND_RATE should be correct");
+        Limits {
+            cooldowns,
+            default_cooldown,
+            guild_limit,
+            guild_period,
+        }
+    })
+}
+
+/// Returns cooldown of command.
+///
+fn cooldown(command_name: &str) -> Duration {
+    let limits: &Limits = limits();
+    limits
+        .cooldowns
+        .get(command_name)
+        .copied()
+        .unwrap_or(limits.default_cooldown)
+}
+
+/// Checks whether cooldown of command has passed for user, without recording its use.
+///
+fn check_command(
+    usages: &mut HashMap<(UserId, String), Usage>,
+    user_id: UserId,
+    command_name: &str,
+    now: Instant,
+) -> Verdict {
+    let cooldown: Duration = cooldown(command_name);
+    if cooldown.is_zero() {
+        return Verdict::Allow;
+    }
+
+    match usages.get_mut(&(user_id, command_name.to_string())) {
+        Some(usage) if now.duration_since(usage.used_at) < cooldown => {
+            if usage.notified {
+                return Verdict::Ignore;
+            }
+            usage.notified = true;
+            Verdict::Wait(cooldown - now.duration_since(usage.used_at))
+        }
+        _ => Verdict::Allow,
+    }
+}
+
+/// Removes usages whose longest cooldown has passed, at most once per `PRUNE_INTERVAL`,
+/// so that commands do not scan usages of all users every time.
+///
+fn prune_usages(usages: &mut HashMap<(UserId, String), Usage>, now: Instant) {
+    let mut pruned_at = PRUNED_AT
+        .get_or_init(|| Mutex::new(now))
+        .lock()
+        .expect("Lock should not be poisoned");
+    if now.duration_since(*pruned_at) < PRUNE_INTERVAL {
+        return;
+    }
+    *pruned_at = now;
+
+    let max_cooldown: Duration = limits().max_cooldown();
+    usages.retain(|_, usage| now.duration_since(usage.used_at) < max_cooldown);
+}
+
+/// Records use of command by user, so that its cooldown starts.
+///
+fn record_command(
+    usages: &mut HashMap<(UserId, String), Usage>,
+    user_id: UserId,
+    command_name: &str,
+    now: Instant,
+) {
+    if cooldown(command_name).is_zero() {
+        return;
+    }
+    prune_usages(usages, now);
+    let _ = usages.insert(
+        (user_id, command_name.to_string()),
+        Usage {
+            used_at: now,
+            notified: false,
+        },
+    );
+}
+
+/// Checks whether rate limit of guild allows one more command, without recording it.
+///
+fn check_guild(
+    guilds: &mut HashMap<GuildId, (VecDeque<Instant>, bool)>,
+    guild_id: GuildId,
+    now: Instant,
+) -> Verdict {
+    let limits: &Limits = limits();
+    if limits.guild_limit == 0 || limits.guild_period.is_zero() {
+        return Verdict::Allow;
+    }
+
+    let (commands, notified): &mut (VecDeque<Instant>, bool) = guilds
+        .entry(guild_id)
+        .or_insert_with(|| (VecDeque::new(), false));
+    while commands
+        .front()
+        .is_some_and(|used_at| now.duration_since(*used_at) >= limits.guild_period)
+    {
+        let _ = commands.pop_front();
+    }
+    if commands.len() < limits.guild_limit {
+        return Verdict::Allow;
+    }
+    if *notified {
+        return Verdict::Ignore;
+    }
+    *notified = true;
+    let oldest: Instant = *commands
+        .front()
+        .expect("Guild has reached limit, so it has commands");
+    Verdict::Wait(limits.guild_period - now.duration_since(oldest))
+}
+
+/// Records command in guild, so that it counts towards rate limit.
+///
+fn record_guild(
+    guilds: &mut HashMap<GuildId, (VecDeque<Instant>, bool)>,
+    guild_id: GuildId,
+    now: Instant,
+) {
+    let limits: &Limits = limits();
+    if limits.guild_limit == 0 || limits.guild_period.is_zero() {
+        return;
+    }
+    let (commands, notified): &mut (VecDeque<Instant>, bool) = guilds
+        .entry(guild_id)
+        .or_insert_with(|| (VecDeque::new(), false));
+    commands.push_back(now);
+    *notified = false;
+}
+
+/// Checks cooldown of user and rate limit of guild, and records use of command only if both
+/// allow it, so that rejected commands do not start cooldown or count towards rate limit.
+///
+/// Returns catalog key of message that explains verdict to user.
+///
+fn use_command(
+    user_id: UserId,
+    guild_id: Option<GuildId>,
+    command_name: &str,
+) -> (&'static str, Verdict) {
+    let now: Instant = Instant::now();
+    let mut usages: MutexGuard<'_, HashMap<(UserId, String), Usage>> = USAGES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned");
+    let mut guilds: MutexGuard<'_, HashMap<GuildId, (VecDeque<Instant>, bool)>> = GUILD_USAGES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned");
+
+    match check_command(&mut usages, user_id, command_name, now) {
+        Verdict::Allow => {}
+        verdict => return ("cooldown.user", verdict),
+    }
+    if let Some(guild_id) = guild_id {
+        match check_guild(&mut guilds, guild_id, now) {
+            Verdict::Allow => record_guild(&mut guilds, guild_id, now),
+            verdict => return ("cooldown.guild", verdict),
+        }
+    }
+    record_command(&mut usages, user_id, command_name, now);
+    ("cooldown.user", Verdict::Allow)
+}
+
+/// Checks that user and guild are allowed to use command now and records its use,
+/// replying with time to wait otherwise.
+///
+/// Should be the last check of `before` hook, because command is dispatched once it passes.
+///
+pub async fn check(ctx: &Context, message: &Message, command_name: &str) -> bool {
+    if Bot::OWNERS.contains(&message.author.id) {
+        return true;
+    }
+
+    let (key, verdict): (&str, Verdict) =
+        use_command(message.author.id, message.guild_id, command_name);
+    let wait: Duration = match verdict {
+        Verdict::Allow => return true,
+        Verdict::Wait(wait) => wait,
+        Verdict::Ignore => return false,
+    };
+
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let seconds: u64 = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+    let _ = reply::warning(
+        ctx,
+        message,
+        &tr(
+            language,
+            key,
+            &[("command", &command_name), ("seconds", &seconds)],
+        ),
+    )
+    .await;
+    false
+}
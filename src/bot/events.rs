@@ -2,87 +2,113 @@
 //!
 
 use crate::{
-    bot::music_playing::*,
+    bot::{
+        audit, automod,
+        locale::{self, tr},
+        music_playing::*,
+        raid::{self, JoinVerdict},
+        reply, scheduler, shutdown,
+    },
     db::{
+        cache,
         connections::SERVERS_DB,
-        models::{Id, Setting, UnregisteredMember},
+        guilds,
+        models::{DbId, Id, Language, LogCategory, Setting, UnregisteredMember},
     },
-    logger,
+    logger, metrics,
 };
 use serenity::{
     async_trait,
     client::{Context, EventHandler},
     model::{
-        channel::Message,
+        application::interaction::{Interaction, InteractionResponseType},
+        channel::{GuildChannel, Message},
+        event::MessageUpdateEvent,
         gateway::Ready,
-        guild::{Guild, Member, UnavailableGuild},
-        id::{ChannelId, GuildId, RoleId, UserId},
+        guild::{Guild, Member, Role, UnavailableGuild},
+        id::{ChannelId, GuildId, MessageId, RoleId, UserId},
+        user::User,
+        voice::VoiceState,
     },
 };
-use sqlx::SqlitePool;
+use sqlx::AnyPool;
+
+/// Returns message that is sent to new members to start their registration.
+///
+pub fn welcome_message(language: Language, guild_name: &str) -> String {
+    tr(
+        language,
+        "events.welcome",
+        &[("guild", &guild_name), ("prefix", &crate::MAIN_BOT_PREFIX)],
+    )
+}
 
 /// `Handler` struct implements `EventHandler` trait for main bot.
 ///
 pub struct Handler;
 #[async_trait]
 impl EventHandler for Handler {
-    async fn ready(&self, _: Context, ready: Ready) {
+    async fn ready(&self, ctx: Context, ready: Ready) {
         logger::log(
             log::Level::Info,
             &format!("{} is connected!", ready.user.name),
         );
+        scheduler::start(ctx.http.clone(), ready.user.id);
+        cache::start_reporting();
     }
 
     async fn guild_create(&self, _: Context, guild: Guild, _: bool) {
-        let connection: &SqlitePool = SERVERS_DB
+        let _timer: metrics::Timer = metrics::Timer::event("guild_create");
+        let connection: &AnyPool = SERVERS_DB
             .get()
             .expect("Connection should be established at this point");
 
         let guild_id: Id = guild.id.into();
-        if sqlx::query("SELECT discord_id FROM guilds WHERE discord_id = ?")
-            .bind(guild_id.to_string())
-            .fetch_optional(connection)
+        if metrics::timed("guilds.find", guilds::is_registered(connection, guild_id))
             .await
             .expect("Query should be correct")
-            .is_some()
         {
             logger::log(log::Level::Info, &format!("On {} guild ready", guild.name));
-            sqlx::query("UPDATE music_bots SET on_channel_id = NULL WHERE guild_id = ?").bind(guild_id.to_string()).execute(connection).await.expect("Query should be correct");
+            metrics::timed(
+                "music_bots.reset",
+                sqlx::query("UPDATE music_bots SET on_channel_id = NULL WHERE guild_id = $1")
+                    .bind(guild_id.db_id())
+                    .execute(connection),
+            )
+            .await
+            .expect("Query should be correct");
+            let _ = cache::load(guild.id).await;
             return;
         }
-        sqlx::query(
-            "
-            INSERT INTO settings VALUES (NULL, NULL, NULL, NULL, NULL, NULL);
-            INSERT INTO guilds VALUES (?, (SELECT last_insert_rowid()));
-            INSERT INTO music_bots VALUES (?, 'music1 ', NULL), (?, 'music2 ', NULL), (?, 'music3 ', NULL);
-        ",
-        )
-        .bind(guild_id.to_string())
-        .bind(guild_id.to_string())
-        .bind(guild_id.to_string())
-        .bind(guild_id.to_string())
-        .execute(connection)
-        .await
-        .expect("Query should be correct");
+        metrics::timed("guilds.register", guilds::register(connection, guild_id))
+            .await
+            .expect("Guild should be registered");
         logger::log(
             log::Level::Info,
             &format!("Registered {} guild", guild.name),
         );
+        let _ = cache::load(guild.id).await;
         logger::log(log::Level::Info, &format!("On {} guild ready", guild.name));
     }
 
     async fn guild_delete(&self, _: Context, incomplete: UnavailableGuild, _: Option<Guild>) {
-        let connection: &SqlitePool = SERVERS_DB
+        let _timer: metrics::Timer = metrics::Timer::event("guild_delete");
+        let connection: &AnyPool = SERVERS_DB
             .get()
             .expect("Connection should be established at this point");
 
-        sqlx::query(
-            "DELETE FROM settings WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = ?)",
+        metrics::timed(
+            "settings.delete",
+            sqlx::query(
+                "DELETE FROM settings WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = $1)",
+            )
+            .bind(incomplete.id.db_id())
+            .execute(connection),
         )
-        .bind(incomplete.id.to_string())
-        .execute(connection)
         .await
         .expect("Query should be correct");
+        cache::invalidate(incomplete.id);
+        automod::invalidate(incomplete.id);
         logger::log(
             log::Level::Info,
             &format!("Unregistered MAIN_BOT from '{}' guild", incomplete.id),
@@ -90,20 +116,60 @@ impl EventHandler for Handler {
     }
 
     async fn guild_member_addition(&self, ctx: Context, member: Member) {
-        let connection: &SqlitePool = SERVERS_DB
+        let _timer: metrics::Timer = metrics::Timer::event("guild_member_addition");
+        let connection: &AnyPool = SERVERS_DB
             .get()
             .expect("Connection should be established at this moment");
 
-        logger::log_discord(
-            &ctx.http,
-            member.guild_id,
-            "guild_member_addition was called",
-        )
-        .await;
+        audit::member_join(&ctx, &member).await;
+        metrics::increment(metrics::Counter::Registrations, "joined");
 
-        let message: String = format!("Welcome to '{}' server!\nPlease, dm me your real name in following form -> '-name <your_name>', e.g. '-name Ваня'", member.guild_id.name(&ctx.cache).expect("This can be called only on guild"));
+        match raid::on_member_join(&ctx, &member).await {
+            JoinVerdict::Allow => {}
+            JoinVerdict::Pause => {
+                metrics::increment(metrics::Counter::Registrations, "paused");
+                metrics::timed(
+                    "unregistered_members.pause",
+                    sqlx::query(
+                        "INSERT INTO unregistered_members (discord_id, guild_id, is_paused) VALUES ($1, $2, 1)",
+                    )
+                    .bind(member.user.id.db_id())
+                    .bind(member.guild_id.db_id())
+                    .execute(connection),
+                )
+                .await
+                .expect("Query should be correct");
+                return;
+            }
+            JoinVerdict::Quarantine => {
+                metrics::increment(metrics::Counter::Registrations, "quarantined");
+                metrics::timed(
+                    "unregistered_members.quarantine",
+                    sqlx::query(
+                        "INSERT INTO unregistered_members (discord_id, guild_id, is_paused, is_quarantined) VALUES ($1, $2, 1, 1)",
+                    )
+                    .bind(member.user.id.db_id())
+                    .bind(member.guild_id.db_id())
+                    .execute(connection),
+                )
+                .await
+                .expect("Query should be correct");
+                return;
+            }
+        }
+
+        let language: Language = locale::user_language(Some(member.guild_id), member.user.id).await;
+        let message: String = welcome_message(
+            language,
+            &member
+                .guild_id
+                .name(&ctx.cache)
+                .expect("This can be called only on guild"),
+        );
         if let Err(why) = member.user.dm(&ctx, |m| m.content(&message)).await {
-            let Setting { member_role_id, .. } = sqlx::query_as::<_, Setting>("SELECT member_role_id FROM settings WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = ?)").bind(member.guild_id.to_string()).fetch_one(connection).await.expect("Query should be correct");
+            let Setting { member_role_id, .. } = cache::get(member.guild_id)
+                .await
+                .expect("Guild should be registered at this moment");
             let member_role_id: RoleId = RoleId::from(Id(member_role_id
                 .0
                 .expect("member_role_id should be set at this moment")));
@@ -119,52 +185,225 @@ impl EventHandler for Handler {
                         .nickname(format!("<{}>", member.user.name))
                 })
                 .await;
+            metrics::increment(metrics::Counter::Registrations, "auto_registered");
             logger::log_discord(
                 &ctx.http,
                 member.guild_id,
+                LogCategory::Registration,
                 &format!(
                     "Registered new member('{}') due to {} error",
                     member.user.name, why
                 ),
-            )
-            .await;
+            );
         } else {
-            sqlx::query("INSERT INTO unregistered_members VALUES (?, ?)")
-                .bind(member.user.id.to_string())
-                .bind(member.guild_id.to_string())
-                .execute(connection)
-                .await
-                .expect("Query should be correct");
+            metrics::increment(metrics::Counter::Registrations, "welcomed");
+            metrics::timed(
+                "unregistered_members.insert",
+                sqlx::query(
+                    "INSERT INTO unregistered_members (discord_id, guild_id, is_paused) VALUES ($1, $2, 0)",
+                )
+                .bind(member.user.id.db_id())
+                .bind(member.guild_id.db_id())
+                .execute(connection),
+            )
+            .await
+            .expect("Query should be correct");
         }
     }
 
+    async fn guild_member_removal(
+        &self,
+        ctx: Context,
+        guild_id: GuildId,
+        user: User,
+        _: Option<Member>,
+    ) {
+        let _timer: metrics::Timer = metrics::Timer::event("guild_member_removal");
+        audit::member_leave(&ctx, guild_id, &user).await;
+    }
+
+    async fn guild_member_update(&self, ctx: Context, old: Option<Member>, new: Member) {
+        let _timer: metrics::Timer = metrics::Timer::event("guild_member_update");
+        audit::member_update(&ctx, old.as_ref(), &new).await;
+    }
+
+    async fn message_update(
+        &self,
+        ctx: Context,
+        _: Option<Message>,
+        _: Option<Message>,
+        event: MessageUpdateEvent,
+    ) {
+        let _timer: metrics::Timer = metrics::Timer::event("message_update");
+        audit::message_edit(&ctx, &event).await;
+    }
+
+    async fn message_delete(
+        &self,
+        ctx: Context,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        guild_id: Option<GuildId>,
+    ) {
+        let _timer: metrics::Timer = metrics::Timer::event("message_delete");
+        audit::message_delete(&ctx, guild_id, channel_id, message_id).await;
+    }
+
+    async fn channel_create(&self, ctx: Context, channel: &GuildChannel) {
+        let _timer: metrics::Timer = metrics::Timer::event("channel_create");
+        audit::channel_change(&ctx, channel, true).await;
+    }
+
+    async fn channel_delete(&self, ctx: Context, channel: &GuildChannel) {
+        let _timer: metrics::Timer = metrics::Timer::event("channel_delete");
+        audit::channel_change(&ctx, channel, false).await;
+    }
+
+    async fn guild_role_create(&self, ctx: Context, role: Role) {
+        let _timer: metrics::Timer = metrics::Timer::event("guild_role_create");
+        audit::role_create(&ctx, &role).await;
+    }
+
+    async fn guild_role_delete(
+        &self,
+        ctx: Context,
+        guild_id: GuildId,
+        role_id: RoleId,
+        role: Option<Role>,
+    ) {
+        let _timer: metrics::Timer = metrics::Timer::event("guild_role_delete");
+        audit::role_delete(&ctx, guild_id, role_id, role.as_ref()).await;
+    }
+
+    async fn voice_state_update(&self, ctx: Context, old: Option<VoiceState>, new: VoiceState) {
+        let _timer: metrics::Timer = metrics::Timer::event("voice_state_update");
+        audit::voice_move(&ctx, old.as_ref(), &new).await;
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        let _timer: metrics::Timer = metrics::Timer::event("interaction_create");
+        let component = match interaction {
+            Interaction::MessageComponent(component) => component,
+            _ => return,
+        };
+        if component.data.custom_id != raid::LIFT_LOCKDOWN_BUTTON {
+            return;
+        }
+        let guild_id: GuildId = match component.guild_id {
+            Some(guild_id) => guild_id,
+            None => return,
+        };
+
+        let is_allowed: bool = component
+            .member
+            .as_ref()
+            .and_then(|member| member.permissions)
+            .is_some_and(|permissions| permissions.administrator());
+        let key: &str = if !is_allowed {
+            "events.lift_forbidden"
+        } else if raid::lift_lockdown(&ctx, guild_id, component.user.id).await {
+            "events.lockdown_lifted"
+        } else {
+            "raid.not_locked_down"
+        };
+        let language: Language = locale::user_language(Some(guild_id), component.user.id).await;
+        let _ = component
+            .create_interaction_response(&ctx.http, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|d| {
+                        d.content(tr(language, key, &[])).ephemeral(true)
+                    })
+            })
+            .await;
+    }
+
     async fn message(&self, ctx: Context, message: Message) {
+        let _timer: metrics::Timer = metrics::Timer::event("message");
         if message.guild_id.is_some() {
+            audit::remember_message(&message);
+            automod::check(&ctx, &message).await;
             return;
         }
 
-        let connection: &SqlitePool = SERVERS_DB
+        let connection: &AnyPool = SERVERS_DB
             .get()
             .expect("Connection should be established at this moment");
 
-        let command: Vec<&str> = message.content[crate::MAIN_BOT_PREFIX.len()..]
-            .split_whitespace()
-            .collect::<Vec<&str>>();
-        let members: Vec<UnregisteredMember> = sqlx::query_as::<_, UnregisteredMember>(
-            "SELECT * FROM unregistered_members WHERE discord_id = ?",
+        if message.author.bot {
+            return;
+        }
+        let command: Vec<&str> = match message.content.strip_prefix(crate::MAIN_BOT_PREFIX) {
+            Some(content) => content.split_whitespace().collect::<Vec<&str>>(),
+            None => return,
+        };
+        let members: Vec<UnregisteredMember> = metrics::timed(
+            "unregistered_members.get",
+            sqlx::query_as::<_, UnregisteredMember>(
+                "SELECT * FROM unregistered_members WHERE discord_id = $1 AND is_paused = 0",
+            )
+            .bind(message.author.id.db_id())
+            .fetch_all(connection),
         )
-        .bind(message.author.id.to_string())
-        .fetch_all(connection)
         .await
         .expect("Query should be correct");
-        match command[0] {
-            "name" => {
+        let language: Language = locale::user_language(
+            members.first().map(|member| GuildId::from(member.guild_id)),
+            message.author.id,
+        )
+        .await;
+        match command.first().copied() {
+            Some("name") => {
+                let name: &str = match command.get(1).copied() {
+                    Some(name) => name,
+                    None => {
+                        let _ = reply::warning(
+                            &ctx,
+                            &message,
+                            &tr(
+                                language,
+                                "events.name_missing",
+                                &[("prefix", &crate::MAIN_BOT_PREFIX)],
+                            ),
+                        )
+                        .await;
+                        return;
+                    }
+                };
+                if members.is_empty() {
+                    let paused_member: Option<UnregisteredMember> = metrics::timed(
+                        "unregistered_members.get_paused",
+                        sqlx::query_as::<_, UnregisteredMember>(
+                            "SELECT * FROM unregistered_members WHERE discord_id = $1 AND is_paused = 1",
+                        )
+                        .bind(message.author.id.db_id())
+                        .fetch_optional(connection),
+                    )
+                    .await
+                    .expect("Query should be correct");
+                    let (language, key): (Language, &str) = match paused_member {
+                        Some(member) => (
+                            locale::user_language(
+                                Some(GuildId::from(member.guild_id)),
+                                message.author.id,
+                            )
+                            .await,
+                            "events.registration_paused",
+                        ),
+                        None => (language, "events.not_registering"),
+                    };
+                    let _ = reply::warning(&ctx, &message, &tr(language, key, &[])).await;
+                    return;
+                }
+
+                let count: usize = members.len();
                 for member in members {
                     let member: Member = GuildId::from(member.guild_id)
                         .member(&ctx, UserId::from(member.discord_id))
                         .await
                         .expect("Member data should be correct");
-                    let Setting { member_role_id, .. } = sqlx::query_as::<_, Setting>("SELECT member_role_id FROM settings WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = ?)").bind(member.guild_id.to_string()).fetch_one(connection).await.expect("Query should be correct");
+                    let Setting { member_role_id, .. } = cache::get(member.guild_id)
+                        .await
+                        .expect("Guild should be registered at this moment");
                     let member_role_id: RoleId = RoleId::from(Id(member_role_id
                         .0
                         .expect("member_role_id should be set at this moment")));
@@ -177,21 +416,38 @@ impl EventHandler for Handler {
                         .guild_id
                         .edit_member(&ctx.http, member.user.id, |m| {
                             m.roles(&member_roles)
-                                .nickname(format!("{} <{}>", command[1], member.user.name))
+                                .nickname(format!("{} <{}>", name, member.user.name))
                         })
                         .await;
-                    sqlx::query("DELETE FROM unregistered_members WHERE discord_id = ?")
-                        .bind(member.user.id.to_string())
-                        .execute(connection)
-                        .await
-                        .expect("Query should be correct");
+                    metrics::timed(
+                        "unregistered_members.delete",
+                        sqlx::query(
+                            "DELETE FROM unregistered_members WHERE discord_id = $1 AND guild_id = $2",
+                        )
+                        .bind(member.user.id.db_id())
+                        .bind(member.guild_id.db_id())
+                        .execute(connection),
+                    )
+                    .await
+                    .expect("Query should be correct");
+                    metrics::increment(metrics::Counter::Registrations, "registered");
                     logger::log_discord(
                         &ctx.http,
                         member.guild_id,
+                        LogCategory::Registration,
                         &format!("Registered new member('{}')", member.user.name),
-                    )
-                    .await;
+                    );
                 }
+                let _ = reply::success(
+                    &ctx,
+                    &message,
+                    &tr(
+                        language,
+                        "events.registered",
+                        &[("name", &name), ("count", &count)],
+                    ),
+                )
+                .await;
             }
             _ => {}
         }
@@ -199,12 +455,10 @@ impl EventHandler for Handler {
 }
 
 async fn check_music_log_channel(guild_id: GuildId, channel_id: ChannelId) -> bool {
-    let connection: &SqlitePool = SERVERS_DB
-        .get()
-        .expect("Connection should be established at this moment");
-
-    let Setting { music_log_channel_id, .. } = sqlx::query_as::<_, Setting>("SELECT music_log_channel_id FROM settings WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = ?)").bind(guild_id.to_string()).fetch_one(connection).await.expect("Query should be correct");
-    if let Some(music_log_channel_id) = music_log_channel_id.0 {
+    let music_log_channel_id: Option<u64> = cache::get(guild_id)
+        .await
+        .and_then(|setting| setting.music_log_channel_id.0);
+    if let Some(music_log_channel_id) = music_log_channel_id {
         if music_log_channel_id == channel_id.0 {
             return true;
         }
@@ -223,14 +477,17 @@ impl EventHandler for MusicHandler1 {
     }
 
     async fn guild_delete(&self, _: Context, incomplete: UnavailableGuild, _: Option<Guild>) {
-        let connection: &SqlitePool = SERVERS_DB
+        let connection: &AnyPool = SERVERS_DB
             .get()
             .expect("Connection should be established at this point");
 
-        sqlx::query("DELETE FROM music_bots WHERE prefix = ? AND guild_id = ?")
-        .bind(crate::MUSIC_BOT_PREFIXES[0].to_string())
-        .bind(incomplete.id.to_string())
-        .execute(connection)
+        metrics::timed(
+            "music_bots.delete",
+            sqlx::query("DELETE FROM music_bots WHERE prefix = $1 AND guild_id = $2")
+                .bind(crate::MUSIC_BOT_PREFIXES[0].to_string())
+                .bind(incomplete.id.db_id())
+                .execute(connection),
+        )
         .await
         .expect("Query should be correct");
         logger::log(
@@ -241,6 +498,7 @@ impl EventHandler for MusicHandler1 {
 
     async fn message(&self, ctx: Context, message: Message) {
         if message.guild_id.is_none()
+            || shutdown::is_shutting_down()
             || !check_music_log_channel(
                 message.guild_id.expect("First condition exclusives None"),
                 message.channel_id,
@@ -250,6 +508,7 @@ impl EventHandler for MusicHandler1 {
         {
             return;
         }
+        let _timer: metrics::Timer = metrics::Timer::event("music_order");
         let command: Vec<&str> = message.content.split_whitespace().collect::<Vec<&str>>();
         match command[1] {
             "play" => play(&ctx, &message, MusicOrder::from(command[2..].join(" "))).await,
@@ -285,14 +544,17 @@ impl EventHandler for MusicHandler2 {
     }
 
     async fn guild_delete(&self, _: Context, incomplete: UnavailableGuild, _: Option<Guild>) {
-        let connection: &SqlitePool = SERVERS_DB
+        let connection: &AnyPool = SERVERS_DB
             .get()
             .expect("Connection should be established at this point");
 
-        sqlx::query("DELETE FROM music_bots WHERE prefix = ? AND guild_id = ?")
-        .bind(crate::MUSIC_BOT_PREFIXES[1].to_string())
-        .bind(incomplete.id.to_string())
-        .execute(connection)
+        metrics::timed(
+            "music_bots.delete",
+            sqlx::query("DELETE FROM music_bots WHERE prefix = $1 AND guild_id = $2")
+                .bind(crate::MUSIC_BOT_PREFIXES[1].to_string())
+                .bind(incomplete.id.db_id())
+                .execute(connection),
+        )
         .await
         .expect("Query should be correct");
         logger::log(
@@ -303,6 +565,7 @@ impl EventHandler for MusicHandler2 {
 
     async fn message(&self, ctx: Context, message: Message) {
         if message.guild_id.is_none()
+            || shutdown::is_shutting_down()
             || !check_music_log_channel(
                 message.guild_id.expect("First condition exclusives None"),
                 message.channel_id,
@@ -312,6 +575,7 @@ impl EventHandler for MusicHandler2 {
         {
             return;
         }
+        let _timer: metrics::Timer = metrics::Timer::event("music_order");
         let command: Vec<&str> = message.content.split_whitespace().collect::<Vec<&str>>();
         match command[1] {
             "play" => play(&ctx, &message, MusicOrder::from(command[2..].join(" "))).await,
@@ -347,14 +611,17 @@ impl EventHandler for MusicHandler3 {
     }
 
     async fn guild_delete(&self, _: Context, incomplete: UnavailableGuild, _: Option<Guild>) {
-        let connection: &SqlitePool = SERVERS_DB
+        let connection: &AnyPool = SERVERS_DB
             .get()
             .expect("Connection should be established at this point");
 
-        sqlx::query("DELETE FROM music_bots WHERE prefix = ? AND guild_id = ?")
-        .bind(crate::MUSIC_BOT_PREFIXES[2].to_string())
-        .bind(incomplete.id.to_string())
-        .execute(connection)
+        metrics::timed(
+            "music_bots.delete",
+            sqlx::query("DELETE FROM music_bots WHERE prefix = $1 AND guild_id = $2")
+                .bind(crate::MUSIC_BOT_PREFIXES[2].to_string())
+                .bind(incomplete.id.db_id())
+                .execute(connection),
+        )
         .await
         .expect("Query should be correct");
         logger::log(
@@ -365,6 +632,7 @@ impl EventHandler for MusicHandler3 {
 
     async fn message(&self, ctx: Context, message: Message) {
         if message.guild_id.is_none()
+            || shutdown::is_shutting_down()
             || !check_music_log_channel(
                 message.guild_id.expect("First condition exclusives None"),
                 message.channel_id,
@@ -374,6 +642,7 @@ impl EventHandler for MusicHandler3 {
         {
             return;
         }
+        let _timer: metrics::Timer = metrics::Timer::event("music_order");
         let command: Vec<&str> = message.content.split_whitespace().collect::<Vec<&str>>();
         match command[1] {
             "play" => play(&ctx, &message, MusicOrder::from(command[2..].join(" "))).await,
@@ -0,0 +1,337 @@
+//! `help` module implements help command that is generated from command groups
+//! and lists only commands that caller is allowed to use.
+//!
+
+use crate::{
+    bot::{
+        locale::{self, tr},
+        prefix, reply,
+    },
+    db::models::Language,
+    logger,
+};
+use serenity::{
+    client::Context,
+    framework::standard::{
+        macros::help, Args, Command, CommandGroup, CommandOptions, CommandResult, GroupOptions,
+        HelpOptions, OnlyIn,
+    },
+    model::{channel::Message, id::UserId, permissions::Permissions},
+};
+use std::collections::HashSet;
+
+/// Maximal length of discord message.
+///
+const MAX_MESSAGE_LENGTH: usize = 2000;
+
+/// `Caller` struct represents user that called help command.
+///
+struct Caller {
+    /// Whether caller is one of bot owners (owners are allowed to use every command).
+    ///
+    is_owner: bool,
+    /// Permissions of caller in guild, `None` in direct messages.
+    ///
+    permissions: Option<Permissions>,
+}
+impl Caller {
+    /// Returns whether command (or group) with given restrictions is shown to caller.
+    ///
+    fn allows(&self, help_available: bool, owners_only: bool, only_in: OnlyIn) -> bool {
+        let place_allowed: bool = !matches!(
+            (&self.permissions, only_in),
+            (Some(_), OnlyIn::Dm) | (None, OnlyIn::Guild)
+        );
+        help_available && place_allowed && (self.is_owner || !owners_only)
+    }
+
+    /// Returns whether caller is allowed to use command of given group.
+    ///
+    fn can_use(&self, group: &GroupOptions, command: &CommandOptions) -> bool {
+        if !self.allows(group.help_available, group.owners_only, group.only_in)
+            || !self.allows(command.help_available, command.owners_only, command.only_in)
+        {
+            return false;
+        }
+        match self.permissions {
+            Some(permissions) if !self.is_owner => {
+                permissions.administrator()
+                    || permissions
+                        .contains(group.required_permissions | command.required_permissions)
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Returns catalog key of command description, e.g. `help.command.automod.rate`.
+///
+fn description_key(path: &[&str]) -> String {
+    format!("help.command.{}", path.join("."))
+}
+
+/// Returns name of command that is used in help.
+///
+fn command_name(command: &Command) -> &'static str {
+    command.options.names.first().copied().unwrap_or_default()
+}
+
+/// Finds command by words (e.g. `["automod", "rate"]`) among commands that caller can use.
+///
+/// Returns group of command, path to it and command itself.
+///
+fn find_command(
+    groups: &[&'static CommandGroup],
+    caller: &Caller,
+    words: &[String],
+) -> Option<(&'static CommandGroup, Vec<&'static str>, &'static Command)> {
+    for group in groups.iter().copied() {
+        let mut path: Vec<&'static str> = Vec::new();
+        let mut rest: &[String] = words;
+        if !group.options.prefixes.is_empty() {
+            match rest.first().and_then(|word| {
+                group
+                    .options
+                    .prefixes
+                    .iter()
+                    .find(|prefix| **prefix == word.as_str())
+            }) {
+                Some(prefix) => {
+                    path.push(*prefix);
+                    rest = &rest[1..];
+                }
+                None => continue,
+            }
+        }
+
+        let mut command: &'static Command = match rest.first() {
+            Some(word) => match group
+                .options
+                .commands
+                .iter()
+                .find(|command| command.options.names.contains(&word.as_str()))
+            {
+                Some(command) => {
+                    rest = &rest[1..];
+                    command
+                }
+                None => continue,
+            },
+            None => match group.options.default_command {
+                Some(command) => command,
+                None => continue,
+            },
+        };
+        path.push(command_name(command));
+        while let Some(word) = rest.first() {
+            match command
+                .options
+                .sub_commands
+                .iter()
+                .find(|sub_command| sub_command.options.names.contains(&word.as_str()))
+            {
+                Some(sub_command) => {
+                    command = sub_command;
+                    path.push(command_name(command));
+                    rest = &rest[1..];
+                }
+                None => break,
+            }
+        }
+
+        if rest.is_empty() && caller.can_use(group.options, command.options) {
+            return Some((group, path, command));
+        }
+    }
+    None
+}
+
+/// Renders list of all commands that caller can use, grouped by command groups.
+///
+fn render_list(
+    language: Language,
+    prefix: &str,
+    groups: &[&'static CommandGroup],
+    caller: &Caller,
+) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for group in groups {
+        let group_prefix: Option<&str> = group.options.prefixes.first().copied();
+        let commands: Vec<String> = group
+            .options
+            .commands
+            .iter()
+            .filter(|command| caller.can_use(group.options, command.options))
+            .map(|command| {
+                let path: Vec<&str> = group_prefix
+                    .into_iter()
+                    .chain([command_name(command)])
+                    .collect::<Vec<&str>>();
+                format!(
+                    "`{}{}` — {}",
+                    prefix,
+                    path.join(" "),
+                    tr(language, &description_key(&path), &[])
+                )
+            })
+            .collect::<Vec<String>>();
+        if commands.is_empty() {
+            continue;
+        }
+
+        lines.push(format!(
+            "**{}**",
+            tr(
+                language,
+                &format!("help.group.{}", group.name.to_lowercase()),
+                &[]
+            )
+        ));
+        lines.extend(commands);
+        lines.push(String::new());
+    }
+    lines.push(tr(language, "help.footer", &[("prefix", &prefix)]));
+    lines
+}
+
+/// Renders detailed description of command: usage, examples, sub-commands and required permissions.
+///
+fn render_command(
+    language: Language,
+    prefix: &str,
+    group: &CommandGroup,
+    path: &[&str],
+    command: &Command,
+    caller: &Caller,
+) -> Vec<String> {
+    let invocation: String = format!("{}{}", prefix, path.join(" "));
+    let mut lines: Vec<String> = vec![format!(
+        "**`{}`** — {}",
+        invocation,
+        tr(language, &description_key(path), &[])
+    )];
+
+    lines.push(format!(
+        "{}: `{}`",
+        tr(language, "help.usage", &[]),
+        command
+            .options
+            .usage
+            .map(|usage| format!("{} {}", invocation, usage))
+            .unwrap_or_else(|| invocation.clone())
+    ));
+    if !command.options.examples.is_empty() {
+        lines.push(format!("{}:", tr(language, "help.examples", &[])));
+        lines.extend(
+            command
+                .options
+                .examples
+                .iter()
+                .map(|example| format!("`{} {}`", invocation, example)),
+        );
+    }
+
+    let sub_commands: Vec<String> = command
+        .options
+        .sub_commands
+        .iter()
+        .filter(|sub_command| caller.can_use(group.options, sub_command.options))
+        .map(|sub_command| format!("`{} {}`", invocation, command_name(sub_command)))
+        .collect::<Vec<String>>();
+    if !sub_commands.is_empty() {
+        lines.push(format!(
+            "{}: {}",
+            tr(language, "help.sub_commands", &[]),
+            sub_commands.join(", ")
+        ));
+    }
+
+    let permissions: Permissions =
+        group.options.required_permissions | command.options.required_permissions;
+    if !permissions.is_empty() {
+        lines.push(format!(
+            "{}: {}",
+            tr(language, "help.permissions", &[]),
+            permissions.get_permission_names().join(", ")
+        ));
+    }
+    lines
+}
+
+/// Joins lines into messages that fit into discord message length limit.
+///
+fn into_messages(lines: Vec<String>) -> Vec<String> {
+    let mut messages: Vec<String> = Vec::new();
+    let mut current: String = String::new();
+    for line in lines {
+        if !current.is_empty() && current.len() + line.len() + 1 > MAX_MESSAGE_LENGTH {
+            messages.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(&line);
+    }
+    if !current.is_empty() {
+        messages.push(current);
+    }
+    messages
+}
+
+#[help]
+pub async fn help(
+    ctx: &Context,
+    message: &Message,
+    args: Args,
+    _: &'static HelpOptions,
+    groups: &[&'static CommandGroup],
+    owners: HashSet<UserId>,
+) -> CommandResult {
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let prefix: String = prefix::get_prefix(message.guild_id).await;
+    let permissions: Option<Permissions> = match message.guild_id {
+        Some(_) => Some(
+            message
+                .member(ctx)
+                .await
+                .ok()
+                .and_then(|member| member.permissions(&ctx.cache).ok())
+                .unwrap_or_else(Permissions::empty),
+        ),
+        None => None,
+    };
+    let caller: Caller = Caller {
+        is_owner: owners.contains(&message.author.id),
+        permissions,
+    };
+
+    let words: Vec<String> = args
+        .rest()
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<String>>();
+    let lines: Vec<String> = if words.is_empty() {
+        render_list(language, &prefix, groups, &caller)
+    } else {
+        match find_command(groups, &caller, &words) {
+            Some((group, path, command)) => {
+                render_command(language, &prefix, group, &path, command, &caller)
+            }
+            None => {
+                let text: String = tr(
+                    language,
+                    "help.unknown_command",
+                    &[("command", &words.join(" ")), ("prefix", &prefix)],
+                );
+                reply::warning(ctx, message, &text).await?;
+                return Ok(());
+            }
+        }
+    };
+    for text in into_messages(lines) {
+        reply::info(ctx, message, &text).await?;
+    }
+
+    logger::log(log::Level::Info, "help was called");
+    Ok(())
+}
@@ -0,0 +1,175 @@
+//! `intents` module declares gateway intents that every bot role needs and checks
+//! that privileged ones are enabled for bot application.
+//!
+//! Intents can be overridden with `MAIN_BOT_INTENTS` and `MUSIC_BOT_INTENTS` variables,
+//! which contain intent names separated by commas (e.g. `GUILDS, GUILD_VOICE_STATES`).
+//!
+
+use crate::logger;
+use serenity::{http::Http, model::gateway::GatewayIntents};
+
+/// Names of intents that can be used in configuration.
+///
+const NAMES: [(&str, GatewayIntents); 16] = [
+    ("GUILDS", GatewayIntents::GUILDS),
+    ("GUILD_MEMBERS", GatewayIntents::GUILD_MEMBERS),
+    (
+        "GUILD_EMOJIS_AND_STICKERS",
+        GatewayIntents::GUILD_EMOJIS_AND_STICKERS,
+    ),
+    ("GUILD_INTEGRATIONS", GatewayIntents::GUILD_INTEGRATIONS),
+    ("GUILD_WEBHOOKS", GatewayIntents::GUILD_WEBHOOKS),
+    ("GUILD_INVITES", GatewayIntents::GUILD_INVITES),
+    ("GUILD_VOICE_STATES", GatewayIntents::GUILD_VOICE_STATES),
+    ("GUILD_PRESENCES", GatewayIntents::GUILD_PRESENCES),
+    ("GUILD_MESSAGES", GatewayIntents::GUILD_MESSAGES),
+    (
+        "GUILD_MESSAGE_REACTIONS",
+        GatewayIntents::GUILD_MESSAGE_REACTIONS,
+    ),
+    ("GUILD_MESSAGE_TYPING", GatewayIntents::GUILD_MESSAGE_TYPING),
+    ("DIRECT_MESSAGES", GatewayIntents::DIRECT_MESSAGES),
+    (
+        "DIRECT_MESSAGE_REACTIONS",
+        GatewayIntents::DIRECT_MESSAGE_REACTIONS,
+    ),
+    (
+        "DIRECT_MESSAGE_TYPING",
+        GatewayIntents::DIRECT_MESSAGE_TYPING,
+    ),
+    ("MESSAGE_CONTENT", GatewayIntents::MESSAGE_CONTENT),
+    (
+        "GUILD_SCHEDULED_EVENTS",
+        GatewayIntents::GUILD_SCHEDULED_EVENTS,
+    ),
+];
+
+/// Privileged intents, names of their switches in developer portal
+/// and bits of application flags that tell whether they are enabled (either flag is sufficient).
+///
+const PRIVILEGED: [(GatewayIntents, &str, u64, u64); 3] = [
+    (
+        GatewayIntents::GUILD_PRESENCES,
+        "Presence Intent",
+        1 << 12,
+        1 << 13,
+    ),
+    (
+        GatewayIntents::GUILD_MEMBERS,
+        "Server Members Intent",
+        1 << 14,
+        1 << 15,
+    ),
+    (
+        GatewayIntents::MESSAGE_CONTENT,
+        "Message Content Intent",
+        1 << 18,
+        1 << 19,
+    ),
+];
+
+/// `BotRole` enum represents role of bot, which determines intents that it needs.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BotRole {
+    /// Main bot that handles commands, registration, moderation and logging.
+    ///
+    Main,
+    /// Music bot that plays music in voice channels on orders from main bot.
+    ///
+    Music,
+}
+impl BotRole {
+    /// Returns intents that bot of this role needs by default.
+    ///
+    pub fn default_intents(&self) -> GatewayIntents {
+        match self {
+            BotRole::Main => {
+                GatewayIntents::GUILDS
+                    | GatewayIntents::GUILD_MEMBERS
+                    | GatewayIntents::GUILD_VOICE_STATES
+                    | GatewayIntents::GUILD_MESSAGES
+                    | GatewayIntents::DIRECT_MESSAGES
+                    | GatewayIntents::MESSAGE_CONTENT
+            }
+            // Orders are plain messages in music log channel, so music bots need to read their content.
+            BotRole::Music => {
+                GatewayIntents::GUILDS
+                    | GatewayIntents::GUILD_VOICE_STATES
+                    | GatewayIntents::GUILD_MESSAGES
+                    | GatewayIntents::MESSAGE_CONTENT
+            }
+        }
+    }
+
+    /// Returns intents of bot of this role, either configured or default ones.
+    ///
+    pub fn intents(&self) -> GatewayIntents {
+        let variable: &str = match self {
+            BotRole::Main => "MAIN_BOT_INTENTS",
+            BotRole::Music => "MUSIC_BOT_INTENTS",
+        };
+        match dotenv::var(variable) {
+            Ok(names) => {
+                parse(&names).expect("Intents should be given as intent names separated by commas")
+            }
+            Err(_) => self.default_intents(),
+        }
+    }
+}
+
+/// Parses intents from names separated by commas.
+///
+pub fn parse(names: &str) -> Result<GatewayIntents, String> {
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .try_fold(GatewayIntents::empty(), |intents, name| {
+            NAMES
+                .iter()
+                .find(|(known, _)| known.eq_ignore_ascii_case(name))
+                .map(|(_, intent)| intents | *intent)
+                .ok_or_else(|| format!("unknown intent '{}'", name))
+        })
+}
+
+/// Checks that privileged intents among given ones are enabled for application of bot
+/// and logs explanation of what should be enabled in developer portal if some of them are not.
+///
+/// Returns `false` if bot would not be able to connect because of missing intents.
+///
+pub async fn check(name: &str, token: &str, intents: GatewayIntents) -> bool {
+    let http: Http = Http::new(token);
+    let flags: u64 = match http.get_current_application_info().await {
+        Ok(info) => info.flags.map_or(0, |flags| u64::from(flags.bits())),
+        Err(error) => {
+            logger::log(
+                log::Level::Warn,
+                &format!("Unable to check intents of {}: {}", name, error),
+            );
+            return true;
+        }
+    };
+
+    let missing: Vec<&str> = PRIVILEGED
+        .iter()
+        .filter(|(intent, _, enabled, limited)| {
+            intents.contains(*intent) && flags & (enabled | limited) == 0
+        })
+        .map(|(_, switch, _, _)| *switch)
+        .collect::<Vec<&str>>();
+    if !missing.is_empty() {
+        logger::log(
+            log::Level::Error,
+            &format!(
+                "{} needs privileged intents that are not enabled for its application: {}. \
+                Enable them on Bot page of application in Discord developer portal \
+                or remove them from intents of bot role",
+                name,
+                missing.join(", ")
+            ),
+        );
+    }
+    missing.is_empty()
+}
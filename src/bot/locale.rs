@@ -0,0 +1,266 @@
+//! `locale` module implements translation of bot replies using message catalogs
+//! with placeholder interpolation and plural rules, and per-guild and per-user language settings.
+//!
+
+use crate::{
+    bot::reply,
+    db::{
+        cache,
+        connections::SERVERS_DB,
+        models::{DbId, Language, UserLanguage},
+    },
+    logger, metrics,
+};
+use serde::Deserialize;
+use serenity::{
+    client::Context,
+    framework::standard::{macros::command, Args, CommandError, CommandResult},
+    model::{
+        channel::Message,
+        id::{GuildId, UserId},
+    },
+};
+use sqlx::AnyPool;
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    str::FromStr,
+    sync::{Mutex, OnceLock},
+    time::Instant,
+};
+
+/// `Entry` enum represents message of catalog.
+///
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    /// Message that does not depend on count.
+    ///
+    Text(String),
+    /// Message that has form for every plural category of language.
+    ///
+    Plural(HashMap<String, String>),
+}
+
+/// Message catalogs of every language.
+///
+static CATALOGS: OnceLock<HashMap<Language, HashMap<String, Entry>>> = OnceLock::new();
+
+/// Cached own languages of users along with the moment they were loaded.
+///
+static USER_LANGUAGES: OnceLock<Mutex<HashMap<UserId, (Instant, Option<Language>)>>> =
+    OnceLock::new();
+
+/// Returns source of message catalog of language.
+///
+fn catalog_source(language: Language) -> &'static str {
+    match language {
+        Language::En => include_str!("../../assets/locales/en.json"),
+        Language::Ru => include_str!("../../assets/locales/ru.json"),
+    }
+}
+
+/// Returns plural category (`one`, `few`, `many` or `other`) of number in given language.
+///
+pub fn plural_category(language: Language, n: i64) -> &'static str {
+    let n: i64 = n.abs();
+    match language {
+        Language::En => {
+            if n == 1 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+        Language::Ru => match (n % 10, n % 100) {
+            (1, rem) if rem != 11 => "one",
+            (2..=4, rem) if !(12..=14).contains(&rem) => "few",
+            _ => "many",
+        },
+    }
+}
+
+/// Translates message with given key, substituting `{name}` placeholders with arguments.
+///
+/// If message has plural forms, form is chosen by `count` argument.
+/// Messages that are missing in catalog fall back to English and then to the key itself.
+///
+pub fn tr(language: Language, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let catalogs: &HashMap<Language, HashMap<String, Entry>> = CATALOGS.get_or_init(|| {
+        Language::ALL
+            .into_iter()
+            .map(|language| {
+                let catalog: HashMap<String, Entry> =
+                    serde_json::from_str(catalog_source(language))
+                        .expect("Message catalog should be correct");
+                (language, catalog)
+            })
+            .collect::<HashMap<Language, HashMap<String, Entry>>>()
+    });
+    let entry: Option<&Entry> = catalogs
+        .get(&language)
+        .and_then(|catalog| catalog.get(key))
+        .or_else(|| {
+            catalogs
+                .get(&Language::default())
+                .and_then(|catalog| catalog.get(key))
+        });
+
+    let template: String = match entry {
+        Some(Entry::Text(text)) => text.clone(),
+        Some(Entry::Plural(forms)) => {
+            let count: i64 = args
+                .iter()
+                .find(|(name, _)| *name == "count")
+                .and_then(|(_, value)| value.to_string().parse::<i64>().ok())
+                .unwrap_or(0);
+            forms
+                .get(plural_category(language, count))
+                .or_else(|| forms.get("other"))
+                .or_else(|| forms.get("many"))
+                .cloned()
+                .unwrap_or_default()
+        }
+        None => {
+            logger::log(
+                log::Level::Warn,
+                &format!("Message '{}' is missing in catalogs", key),
+            );
+            key.to_string()
+        }
+    };
+    args.iter().fold(template, |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), &value.to_string())
+    })
+}
+
+/// Returns language of guild.
+///
+pub async fn guild_language(guild_id: GuildId) -> Language {
+    cache::get(guild_id)
+        .await
+        .and_then(|setting| setting.language)
+        .and_then(|language| Language::from_str(&language).ok())
+        .unwrap_or_default()
+}
+
+/// Returns language that user has set for themselves, if any.
+///
+/// Answers are cached for [`cache::TTL`], including users without own language,
+/// so that replies do not need a database round-trip.
+///
+async fn own_language(user_id: UserId) -> Option<Language> {
+    let cached: Option<Option<Language>> = USER_LANGUAGES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned")
+        .get(&user_id)
+        .filter(|(loaded_at, _)| loaded_at.elapsed() < cache::TTL)
+        .map(|(_, language)| *language);
+    if let Some(language) = cached {
+        return language;
+    }
+
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    let language: Option<Language> = metrics::timed(
+        "user_languages.get",
+        sqlx::query_as::<_, UserLanguage>("SELECT * FROM user_languages WHERE user_id = $1")
+            .bind(user_id.db_id())
+            .fetch_optional(connection),
+    )
+    .await
+    .expect("Query should be correct")
+    .map(|UserLanguage { language, .. }| language);
+    remember_language(user_id, language);
+    language
+}
+
+/// Stores language of user in cache, dropping entries that have expired.
+///
+fn remember_language(user_id: UserId, language: Option<Language>) {
+    let mut languages = USER_LANGUAGES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned");
+    languages.retain(|_, (loaded_at, _)| loaded_at.elapsed() < cache::TTL);
+    languages.insert(user_id, (Instant::now(), language));
+}
+
+/// Returns language that user should be replied in: user's own language if it was set,
+/// otherwise language of guild.
+///
+pub async fn user_language(guild_id: Option<GuildId>, user_id: UserId) -> Language {
+    match (own_language(user_id).await, guild_id) {
+        (Some(language), _) => language,
+        (None, Some(guild_id)) => guild_language(guild_id).await,
+        (None, None) => Language::default(),
+    }
+}
+
+/// Parses language from arguments, or returns error that tells user which languages are known.
+///
+pub async fn parse_language(message: &Message, args: &mut Args) -> Result<Language, CommandError> {
+    let argument: String = args.single::<String>().unwrap_or_default();
+    match Language::from_str(&argument) {
+        Ok(language) => Ok(language),
+        Err(_) => {
+            let reply_language: Language = user_language(message.guild_id, message.author.id).await;
+            let languages: Vec<&str> = Language::ALL
+                .iter()
+                .map(|language| language.as_str())
+                .collect::<Vec<&str>>();
+            Err(reply::user_error(tr(
+                reply_language,
+                "locale.unknown_language",
+                &[
+                    ("language", &argument),
+                    ("languages", &languages.join(", ")),
+                ],
+            )))
+        }
+    }
+}
+
+#[command]
+#[usage("<language>")]
+#[example("ru")]
+#[num_args(1)]
+pub async fn language(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    let language: Language = parse_language(message, &mut args).await?;
+
+    metrics::timed(
+        "user_languages.set",
+        sqlx::query(
+            "
+        INSERT INTO user_languages VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE SET language = excluded.language
+    ",
+        )
+        .bind(message.author.id.db_id())
+        .bind(language.to_string())
+        .execute(connection),
+    )
+    .await
+    .expect("Query should be correct");
+    remember_language(message.author.id, Some(language));
+    reply::success(
+        ctx,
+        message,
+        &tr(
+            language,
+            "locale.user_language_set",
+            &[("language", &language.as_str())],
+        ),
+    )
+    .await?;
+
+    logger::log(log::Level::Info, "language was called");
+    Ok(())
+}
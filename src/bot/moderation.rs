@@ -0,0 +1,862 @@
+//! `moderation` module implements commands that are used to moderate guild members
+//! and keeps record of every taken action as numbered case.
+//!
+
+use crate::{
+    bot::{
+        locale::{self, tr},
+        reply,
+        scheduler::{self, Task},
+    },
+    db::{
+        cache, cases,
+        connections::SERVERS_DB,
+        models::{Case, CaseAction, Id, Language},
+    },
+    logger, metrics,
+};
+use serenity::{
+    client::Context,
+    framework::standard::{
+        macros::{command, group},
+        {Args, CommandError, CommandResult},
+    },
+    http::Http,
+    model::{
+        channel::Message,
+        guild::Guild,
+        id::{ChannelId, GuildId, RoleId, UserId},
+        Timestamp,
+    },
+};
+use sqlx::AnyPool;
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+/// `Moderation` struct is a group for `serenity` framework that contains moderation commands.
+///
+#[group]
+#[only_in(guilds)]
+#[commands(
+    warn, timeout, kick, ban, tempban, unban, temprole, purge, case, cases, reason
+)]
+pub struct Moderation;
+
+/// `TimeSpanParseError` is returned when `TimeSpan` could not be parsed.
+///
+#[derive(Debug)]
+pub struct TimeSpanParseError;
+impl Display for TimeSpanParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "time span should look like '30s', '10m', '2h' or '7d'")
+    }
+}
+impl std::error::Error for TimeSpanParseError {}
+
+/// `TimeSpan` newtype represents duration argument (e.g. `30s`, `10m`, `2h`, `7d`) in seconds.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct TimeSpan(pub u64);
+impl FromStr for TimeSpan {
+    type Err = TimeSpanParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split: usize = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let amount: u64 = s[..split].parse::<u64>().map_err(|_| TimeSpanParseError)?;
+        let multiplier: u64 = match &s[split..] {
+            "" | "s" => 1,
+            "m" => 60,
+            "h" => 60 * 60,
+            "d" => 24 * 60 * 60,
+            "w" => 7 * 24 * 60 * 60,
+            _ => return Err(TimeSpanParseError),
+        };
+        amount
+            .checked_mul(multiplier)
+            .map(TimeSpan)
+            .ok_or(TimeSpanParseError)
+    }
+}
+
+/// Maximal duration of timeout that is allowed by discord (28 days).
+///
+const MAX_TIMEOUT: u64 = 28 * 24 * 60 * 60;
+
+/// Maximal age of messages that discord allows to delete in bulk (14 days).
+///
+const MAX_BULK_DELETE_AGE: i64 = 14 * 24 * 60 * 60;
+
+/// Maximal length of discord message.
+///
+const MAX_MESSAGE_LENGTH: usize = 2000;
+
+/// Maximal number of characters of reason that is shown in case description.
+///
+const MAX_REASON_LENGTH: usize = 1500;
+
+/// Maximal number of characters of reason that is shown in list of cases.
+///
+const MAX_LISTED_REASON_LENGTH: usize = 200;
+
+async fn get_moderation_channel(guild_id: GuildId) -> Option<ChannelId> {
+    cache::get(guild_id)
+        .await
+        .and_then(|setting| setting.moderation_channel_id.0)
+        .map(ChannelId)
+}
+
+/// Returns reason of case, shortened to given number of characters.
+///
+fn shorten_reason(language: Language, case: &Case, max_length: usize) -> String {
+    let reason: String = case
+        .reason
+        .clone()
+        .unwrap_or_else(|| tr(language, "moderation.reason_not_specified", &[]));
+    match reason.char_indices().nth(max_length) {
+        Some((end, _)) => format!("{}…", &reason[..end]),
+        None => reason,
+    }
+}
+
+/// Checks that author of command is allowed to moderate target: target should be neither owner
+/// of guild nor bot itself, and top role of author should be higher than top role of target.
+///
+async fn check_hierarchy(
+    ctx: &Context,
+    message: &Message,
+    guild_id: GuildId,
+    user_id: UserId,
+) -> Result<(), CommandError> {
+    let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+    let guild: Guild = guild_id
+        .to_guild_cached(&ctx.cache)
+        .ok_or("Guild should be cached")?;
+    if user_id == guild.owner_id {
+        return Err(reply::user_error(tr(
+            language,
+            "moderation.target_is_owner",
+            &[],
+        )));
+    }
+    if user_id == ctx.cache.current_user_id() {
+        return Err(reply::user_error(tr(
+            language,
+            "moderation.target_is_bot",
+            &[],
+        )));
+    }
+    if message.author.id == guild.owner_id {
+        return Ok(());
+    }
+
+    let top_role_position = |roles: &[RoleId]| -> i64 {
+        roles
+            .iter()
+            .filter_map(|role_id| guild.roles.get(role_id))
+            .map(|role| role.position)
+            .max()
+            .unwrap_or(0)
+    };
+    let target = match guild.member(ctx, user_id).await {
+        Ok(target) => target,
+        // Users that are not members of guild (e.g. banned by id) have no roles to compare.
+        Err(_) => return Ok(()),
+    };
+    let author = guild.member(ctx, message.author.id).await?;
+    if top_role_position(&author.roles) <= top_role_position(&target.roles) {
+        return Err(reply::user_error(tr(
+            language,
+            "moderation.target_outranks",
+            &[("user", &user_id)],
+        )));
+    }
+    Ok(())
+}
+
+/// Returns human-readable description of a case.
+///
+pub fn describe_case(language: Language, case: &Case) -> String {
+    let target: String = match case.action {
+        CaseAction::Purge => format!("<#{}>", case.target_id.0),
+        _ => format!("<@{}>", case.target_id.0),
+    };
+    let mut description: String = tr(
+        language,
+        "moderation.case",
+        &[
+            ("case", &case.number),
+            ("action", &case.action.as_str()),
+            ("target", &target),
+            ("moderator", &case.moderator_id.0),
+            ("created_at", &case.created_at),
+            ("reason", &shorten_reason(language, case, MAX_REASON_LENGTH)),
+        ],
+    );
+    if let Some(expires_at) = case.expires_at {
+        description.push('\n');
+        description.push_str(&tr(
+            language,
+            "moderation.case_expires",
+            &[("expires_at", &expires_at)],
+        ));
+    }
+    description
+}
+
+/// Opens new case, stores it in database and announces it in moderation channel.
+///
+pub async fn open_case(
+    http: impl AsRef<Http>,
+    guild_id: GuildId,
+    action: CaseAction,
+    target_id: Id,
+    moderator_id: Id,
+    reason: Option<&str>,
+    expires_at: Option<i64>,
+) -> sqlx::Result<Case> {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    let case: Case = metrics::timed(
+        "cases.open",
+        cases::open(
+            connection,
+            guild_id.into(),
+            action,
+            target_id,
+            moderator_id,
+            reason,
+            expires_at,
+        ),
+    )
+    .await?;
+
+    if let Some(moderation_channel_id) = get_moderation_channel(guild_id).await {
+        let language: Language = locale::guild_language(guild_id).await;
+        if let Err(error) = moderation_channel_id
+            .say(&http, describe_case(language, &case))
+            .await
+        {
+            logger::log(
+                log::Level::Info,
+                &format!(
+                    "An error occured while trying to announce case in channel: {}",
+                    error
+                ),
+            );
+        }
+    }
+    logger::log(
+        log::Level::Info,
+        &format!(
+            "Opened case #{} ({}) on '{}' guild",
+            case.number,
+            case.action.as_str(),
+            guild_id
+        ),
+    );
+    Ok(case)
+}
+
+async fn get_case(guild_id: GuildId, number: u64) -> Option<Case> {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+    let _timer: metrics::Timer = metrics::Timer::query("cases.get");
+
+    cases::get(connection, guild_id.into(), number)
+        .await
+        .expect("Query should be correct")
+}
+
+#[command]
+#[usage("<user> [reason]")]
+#[example("@user spam in #general")]
+#[required_permissions("MODERATE_MEMBERS")]
+#[min_args(1)]
+pub async fn warn(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let user_id: UserId = args.single::<UserId>()?;
+    check_hierarchy(ctx, message, guild_id, user_id).await?;
+    let reason: Option<&str> = args.remains();
+
+    let case: Case = open_case(
+        &ctx.http,
+        guild_id,
+        CaseAction::Warn,
+        user_id.into(),
+        message.author.id.into(),
+        reason,
+        None,
+    )
+    .await?;
+    if let Ok(user) = user_id.to_user(&ctx.http).await {
+        let language: Language = locale::user_language(Some(guild_id), user_id).await;
+        let reason: String = reason.map_or_else(
+            || tr(language, "moderation.reason_not_specified", &[]),
+            str::to_string,
+        );
+        let _ = user
+            .dm(&ctx, |m| {
+                m.content(tr(
+                    language,
+                    "moderation.warned_dm",
+                    &[
+                        ("guild", &guild_id.name(&ctx.cache).unwrap_or_default()),
+                        ("reason", &reason),
+                    ],
+                ))
+            })
+            .await;
+    }
+    let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+    reply::success(
+        ctx,
+        message,
+        &tr(
+            language,
+            "moderation.warned",
+            &[("case", &case.number), ("user", &user_id)],
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command]
+#[usage("<user> <duration> [reason]")]
+#[example("@user 10m flood")]
+#[required_permissions("MODERATE_MEMBERS")]
+#[min_args(2)]
+pub async fn timeout(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let user_id: UserId = args.single::<UserId>()?;
+    check_hierarchy(ctx, message, guild_id, user_id).await?;
+    let TimeSpan(duration) = args.single::<TimeSpan>()?;
+    let reason: Option<&str> = args.remains();
+    if duration == 0 || duration > MAX_TIMEOUT {
+        let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+        return Err(reply::user_error(tr(
+            language,
+            "moderation.invalid_timeout",
+            &[],
+        )));
+    }
+
+    let until: Timestamp =
+        Timestamp::from_unix_timestamp(Timestamp::now().unix_timestamp() + duration as i64)?;
+    guild_id
+        .edit_member(&ctx.http, user_id, |m| {
+            m.disable_communication_until_datetime(until)
+        })
+        .await?;
+
+    let case: Case = open_case(
+        &ctx.http,
+        guild_id,
+        CaseAction::Timeout,
+        user_id.into(),
+        message.author.id.into(),
+        reason,
+        Some(until.unix_timestamp()),
+    )
+    .await?;
+    let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+    reply::success(
+        ctx,
+        message,
+        &tr(
+            language,
+            "moderation.timed_out",
+            &[
+                ("case", &case.number),
+                ("user", &user_id),
+                ("until", &until.unix_timestamp()),
+            ],
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command]
+#[usage("<user> [reason]")]
+#[example("@user advertising")]
+#[required_permissions("KICK_MEMBERS")]
+#[min_args(1)]
+pub async fn kick(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let user_id: UserId = args.single::<UserId>()?;
+    check_hierarchy(ctx, message, guild_id, user_id).await?;
+    let reason: Option<&str> = args.remains();
+
+    match reason {
+        Some(reason) => {
+            guild_id
+                .kick_with_reason(&ctx.http, user_id, reason)
+                .await?
+        }
+        None => guild_id.kick(&ctx.http, user_id).await?,
+    }
+
+    let case: Case = open_case(
+        &ctx.http,
+        guild_id,
+        CaseAction::Kick,
+        user_id.into(),
+        message.author.id.into(),
+        reason,
+        None,
+    )
+    .await?;
+    let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+    reply::success(
+        ctx,
+        message,
+        &tr(
+            language,
+            "moderation.kicked",
+            &[("case", &case.number), ("user", &user_id)],
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command]
+#[usage("<user> [reason]")]
+#[example("@user raid")]
+#[required_permissions("BAN_MEMBERS")]
+#[min_args(1)]
+pub async fn ban(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let user_id: UserId = args.single::<UserId>()?;
+    check_hierarchy(ctx, message, guild_id, user_id).await?;
+    let reason: Option<&str> = args.remains();
+
+    match reason {
+        Some(reason) => {
+            guild_id
+                .ban_with_reason(&ctx.http, user_id, 0, reason)
+                .await?
+        }
+        None => guild_id.ban(&ctx.http, user_id, 0).await?,
+    }
+    scheduler::cancel(Task::Unban { guild_id, user_id }).await;
+
+    let case: Case = open_case(
+        &ctx.http,
+        guild_id,
+        CaseAction::Ban,
+        user_id.into(),
+        message.author.id.into(),
+        reason,
+        None,
+    )
+    .await?;
+    let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+    reply::success(
+        ctx,
+        message,
+        &tr(
+            language,
+            "moderation.banned",
+            &[("case", &case.number), ("user", &user_id)],
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command]
+#[usage("<user> <duration> [reason]")]
+#[example("@user 7d toxicity")]
+#[required_permissions("BAN_MEMBERS")]
+#[min_args(2)]
+pub async fn tempban(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let user_id: UserId = args.single::<UserId>()?;
+    check_hierarchy(ctx, message, guild_id, user_id).await?;
+    let TimeSpan(duration) = args.single::<TimeSpan>()?;
+    let reason: Option<&str> = args.remains();
+    if duration == 0 {
+        let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+        return Err(reply::user_error(tr(
+            language,
+            "moderation.invalid_tempban",
+            &[],
+        )));
+    }
+
+    match reason {
+        Some(reason) => {
+            guild_id
+                .ban_with_reason(&ctx.http, user_id, 0, reason)
+                .await?
+        }
+        None => guild_id.ban(&ctx.http, user_id, 0).await?,
+    }
+    let task: Task = Task::Unban { guild_id, user_id };
+    let expires_at: i64 = Timestamp::now().unix_timestamp() + duration as i64;
+    scheduler::cancel(task).await;
+    scheduler::schedule(task, expires_at).await;
+
+    let case: Case = open_case(
+        &ctx.http,
+        guild_id,
+        CaseAction::Ban,
+        user_id.into(),
+        message.author.id.into(),
+        reason,
+        Some(expires_at),
+    )
+    .await?;
+    let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+    reply::success(
+        ctx,
+        message,
+        &tr(
+            language,
+            "moderation.tempbanned",
+            &[
+                ("case", &case.number),
+                ("user", &user_id),
+                ("until", &expires_at),
+            ],
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command]
+#[usage("<user> [reason]")]
+#[example("290146364346597376 appeal accepted")]
+#[required_permissions("BAN_MEMBERS")]
+#[min_args(1)]
+pub async fn unban(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let user_id: UserId = args.single::<UserId>()?;
+    let reason: Option<&str> = args.remains();
+
+    guild_id.unban(&ctx.http, user_id).await?;
+    scheduler::cancel(Task::Unban { guild_id, user_id }).await;
+
+    let case: Case = open_case(
+        &ctx.http,
+        guild_id,
+        CaseAction::Unban,
+        user_id.into(),
+        message.author.id.into(),
+        reason,
+        None,
+    )
+    .await?;
+    let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+    reply::success(
+        ctx,
+        message,
+        &tr(
+            language,
+            "moderation.unbanned",
+            &[("case", &case.number), ("user", &user_id)],
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command]
+#[usage("<user> <role> <duration> [reason]")]
+#[example("@user @Muted 1h")]
+#[required_permissions("MANAGE_ROLES")]
+#[min_args(3)]
+pub async fn temprole(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let user_id: UserId = args.single::<UserId>()?;
+    check_hierarchy(ctx, message, guild_id, user_id).await?;
+    let role_id: RoleId = args.single::<RoleId>()?;
+    let TimeSpan(duration) = args.single::<TimeSpan>()?;
+    let reason: Option<&str> = args.remains();
+    if duration == 0 {
+        let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+        return Err(reply::user_error(tr(
+            language,
+            "moderation.invalid_temprole",
+            &[],
+        )));
+    }
+
+    ctx.http
+        .add_member_role(guild_id.0, user_id.0, role_id.0, reason)
+        .await?;
+    let task: Task = Task::RemoveRole {
+        guild_id,
+        user_id,
+        role_id,
+    };
+    let expires_at: i64 = Timestamp::now().unix_timestamp() + duration as i64;
+    scheduler::cancel(task).await;
+    scheduler::schedule(task, expires_at).await;
+
+    let case: Case = open_case(
+        &ctx.http,
+        guild_id,
+        CaseAction::Role,
+        user_id.into(),
+        message.author.id.into(),
+        reason,
+        Some(expires_at),
+    )
+    .await?;
+    let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+    reply::success(
+        ctx,
+        message,
+        &tr(
+            language,
+            "moderation.temprole_given",
+            &[
+                ("case", &case.number),
+                ("role", &role_id),
+                ("user", &user_id),
+                ("until", &expires_at),
+            ],
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command]
+#[usage("<count> [reason]")]
+#[example("50 spam")]
+#[required_permissions("MANAGE_MESSAGES")]
+#[min_args(1)]
+pub async fn purge(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let count: u64 = args.single::<u64>()?;
+    let reason: Option<&str> = args.remains();
+    if count == 0 || count > 100 {
+        let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+        return Err(reply::user_error(tr(
+            language,
+            "moderation.invalid_purge_count",
+            &[],
+        )));
+    }
+
+    let oldest: i64 = Timestamp::now().unix_timestamp() - MAX_BULK_DELETE_AGE;
+    let messages: Vec<Message> = message
+        .channel_id
+        .messages(&ctx.http, |r| r.before(message.id).limit(count))
+        .await?
+        .into_iter()
+        .filter(|purged| purged.timestamp.unix_timestamp() > oldest)
+        .collect::<Vec<Message>>();
+    match messages.as_slice() {
+        [] => {}
+        [single] => {
+            message
+                .channel_id
+                .delete_message(&ctx.http, single.id)
+                .await?
+        }
+        _ => {
+            message
+                .channel_id
+                .delete_messages(&ctx.http, messages.iter())
+                .await?
+        }
+    }
+
+    let case: Case = open_case(
+        &ctx.http,
+        guild_id,
+        CaseAction::Purge,
+        message.channel_id.into(),
+        message.author.id.into(),
+        reason,
+        None,
+    )
+    .await?;
+    let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+    reply::success(
+        ctx,
+        message,
+        &tr(
+            language,
+            "moderation.purged",
+            &[("case", &case.number), ("count", &messages.len())],
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command]
+#[usage("<number>")]
+#[example("12")]
+#[required_permissions("MODERATE_MEMBERS")]
+#[num_args(1)]
+pub async fn case(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let number: u64 = args.single::<u64>()?;
+
+    let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+    let case: Case = get_case(guild_id, number).await.ok_or_else(|| {
+        reply::user_error(tr(
+            language,
+            "moderation.case_not_found",
+            &[("case", &number)],
+        ))
+    })?;
+    reply::info(ctx, message, &describe_case(language, &case)).await?;
+
+    Ok(())
+}
+
+#[command]
+#[usage("<user>")]
+#[example("@user")]
+#[required_permissions("MODERATE_MEMBERS")]
+#[num_args(1)]
+pub async fn cases(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let user_id: UserId = args.single::<UserId>()?;
+
+    let cases: Vec<Case> = metrics::timed(
+        "cases.list",
+        cases::list(connection, guild_id.into(), user_id.into()),
+    )
+    .await
+    .expect("Query should be correct");
+
+    let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+    if cases.is_empty() {
+        reply::info(
+            ctx,
+            message,
+            &tr(language, "moderation.no_cases", &[("user", &user_id)]),
+        )
+        .await?;
+        return Ok(());
+    }
+    let mut reply: String = tr(
+        language,
+        "moderation.cases",
+        &[("user", &user_id), ("count", &cases.len())],
+    );
+    reply.push('\n');
+    for case in cases {
+        let line: String = format!(
+            "#{} | {} | <t:{}:d> | {}\n",
+            case.number,
+            case.action.as_str(),
+            case.created_at,
+            shorten_reason(language, &case, MAX_LISTED_REASON_LENGTH)
+        );
+        if reply.len() + line.len() > MAX_MESSAGE_LENGTH {
+            reply::info(ctx, message, &reply).await?;
+            reply.clear();
+        }
+        reply.push_str(&line);
+    }
+    reply::info(ctx, message, &reply).await?;
+
+    Ok(())
+}
+
+#[command]
+#[usage("<number> <reason>")]
+#[example("12 spam in voice chat")]
+#[required_permissions("MODERATE_MEMBERS")]
+#[min_args(2)]
+pub async fn reason(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let number: u64 = args.single::<u64>()?;
+    let reason: &str = args.remains().expect("At least two arguments are supplied");
+
+    if !metrics::timed(
+        "cases.update_reason",
+        cases::set_reason(connection, guild_id.into(), number, reason),
+    )
+    .await
+    .expect("Query should be correct")
+    {
+        let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+        return Err(reply::user_error(tr(
+            language,
+            "moderation.case_not_found",
+            &[("case", &number)],
+        )));
+    }
+
+    if let Some(moderation_channel_id) = get_moderation_channel(guild_id).await {
+        let language: Language = locale::guild_language(guild_id).await;
+        moderation_channel_id
+            .say(
+                &ctx.http,
+                tr(
+                    language,
+                    "moderation.reason_updated_by",
+                    &[
+                        ("case", &number),
+                        ("moderator", &message.author.id),
+                        ("reason", &reason),
+                    ],
+                ),
+            )
+            .await?;
+    }
+    let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+    reply::success(
+        ctx,
+        message,
+        &tr(language, "moderation.reason_updated", &[("case", &number)]),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -2,9 +2,15 @@
 //!
 
 use crate::{
+    bot::{
+        locale::{self, tr},
+        reply,
+    },
     db::{
+        cache,
         connections::SERVERS_DB,
-        models::{MusicBot, Setting},
+        models::{Language, LogCategory, MusicBot},
+        music_bots,
     },
     logger,
 };
@@ -12,14 +18,14 @@ use serenity::{
     client::Context,
     framework::standard::{
         macros::{check, command, group},
-        {Args, CommandOptions, CommandResult, Reason},
+        {Args, CommandError, CommandOptions, CommandResult, Reason},
     },
     model::{
         channel::Message,
         id::{ChannelId, GuildId},
     },
 };
-use sqlx::SqlitePool;
+use sqlx::AnyPool;
 
 /// `MusicOrder` struct is a group for `serenity` framework that contains music order commands.
 ///
@@ -37,87 +43,97 @@ async fn check_music_order_channel(
     _: &mut Args,
     _: &CommandOptions,
 ) -> Result<(), Reason> {
-    let connection: &SqlitePool = SERVERS_DB
-        .get()
-        .expect("Connection should be established at this moment");
-
-    let Setting { music_order_channel_id, .. } = sqlx::query_as::<_, Setting>("SELECT music_order_channel_id FROM settings WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = ?)").bind(message.guild_id.expect("This should be called only on guilds").to_string()).fetch_one(connection).await.expect("Query should be correct");
-    if let Some(channel_id) = music_order_channel_id.0 {
+    let music_order_channel_id: Option<u64> = cache::get(
+        message
+            .guild_id
+            .expect("This should be called only on guilds"),
+    )
+    .await
+    .and_then(|setting| setting.music_order_channel_id.0);
+    if let Some(channel_id) = music_order_channel_id {
         if channel_id == message.channel_id.0 {
             return Ok(());
         }
     }
-    Err(Reason::User(String::from("Wrong channel was used")))
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    Err(Reason::User(tr(language, "music.wrong_channel", &[])))
 }
 
-async fn get_music_log_channel(guild_id: GuildId) -> Option<ChannelId> {
-    let connection: &SqlitePool = SERVERS_DB
-        .get()
-        .expect("Connection should be established at this moment");
+/// Builds error that tells user what is wrong with given locale key.
+///
+async fn user_error(message: &Message, key: &str) -> CommandError {
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    reply::user_error(tr(language, key, &[]))
+}
 
-    let Setting { music_log_channel_id, .. } = sqlx::query_as::<_, Setting>("SELECT music_log_channel_id FROM settings WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = ?)").bind(guild_id.to_string()).fetch_one(connection).await.expect("Query should be correct");
-    music_log_channel_id.0.map(ChannelId)
+async fn get_music_log_channel(guild_id: GuildId) -> Option<ChannelId> {
+    cache::get(guild_id)
+        .await
+        .and_then(|setting| setting.music_log_channel_id.0)
+        .map(ChannelId)
 }
 
 #[command]
+#[usage("<song>")]
+#[example("never gonna give you up")]
 #[min_args(1)]
 pub async fn play(ctx: &Context, message: &Message, args: Args) -> CommandResult {
-    let connection: &SqlitePool = SERVERS_DB
+    let connection: &AnyPool = SERVERS_DB
         .get()
         .expect("Connection should be established at this moment");
 
     let guild_id: GuildId = message
         .guild_id
         .expect("This should be called only on guilds");
-    let voice_channel_id: Option<ChannelId> = message
+    let Some(voice_channel_id) = message
         .guild(&ctx.cache)
         .expect("This should be called only on guilds")
         .voice_states
         .get(&message.author.id)
-        .and_then(|voice_state| voice_state.channel_id);
-    if voice_channel_id.is_none() {
-        return Ok(());
-    }
-    let voice_channel_id: ChannelId = voice_channel_id.expect("None case was handled");
+        .and_then(|voice_state| voice_state.channel_id)
+    else {
+        return Err(user_error(message, "music.not_in_voice").await);
+    };
 
     let order: &str = args.remains().expect("At least one argument is supplied");
-    let music_bot: Option<MusicBot> = sqlx::query_as::<_, MusicBot>(
-        "SELECT prefix FROM music_bots WHERE guild_id = ? AND on_channel_id = ?",
+    let music_bot: Option<MusicBot> =
+        music_bots::in_channel(connection, guild_id.into(), voice_channel_id.into())
+            .await
+            .expect("Query should be correct");
+    let Some(MusicBot { prefix, .. }) = music_bot else {
+        return Err(user_error(message, "music.no_bot_in_channel").await);
+    };
+    let Some(channel_id) = get_music_log_channel(guild_id).await else {
+        return Err(user_error(message, "music.no_log_channel").await);
+    };
+
+    channel_id
+        .say(&ctx.http, format!("{}play {}", prefix, order))
+        .await?;
+    let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+    let channel_name: String = voice_channel_id
+        .to_channel(&ctx.http)
+        .await
+        .expect("Channel exists on this guild")
+        .guild()
+        .expect("This channel is on guild")
+        .name;
+    reply::success(
+        ctx,
+        message,
+        &tr(
+            language,
+            "music.playing",
+            &[("order", &order), ("channel", &channel_name)],
+        ),
     )
-    .bind(guild_id.to_string())
-    .bind(voice_channel_id.to_string())
-    .fetch_optional(connection)
-    .await
-    .expect("Query should be correct");
-    if let Some(MusicBot {
-        prefix,
-        ..
-    }) = music_bot
-    {
-        if let Some(channel_id) = get_music_log_channel(guild_id).await {
-            channel_id
-                .say(&ctx.http, format!("{}play {}", prefix, order))
-                .await?;
-            message
-                .channel_id
-                .say(
-                    &ctx.http,
-                    format!(
-                        "➡️ 🎵 Playing 🎶{}🎶 song on '{}' voice channel!!! ⬅️",
-                        order,
-                        voice_channel_id
-                            .to_channel(&ctx.http)
-                            .await
-                            .expect("Channel exists on this guild")
-                            .guild()
-                            .expect("This channel is on guild")
-                            .name
-                    ),
-                )
-                .await?;
-            logger::log_discord(&ctx.http, guild_id, &format!("Called play on {}", prefix)).await;
-        }
-    }
+    .await?;
+    logger::log_discord(
+        &ctx.http,
+        guild_id,
+        LogCategory::Music,
+        &format!("Called play on {}", prefix),
+    );
 
     Ok(())
 }
@@ -125,149 +141,133 @@ pub async fn play(ctx: &Context, message: &Message, args: Args) -> CommandResult
 #[command]
 #[num_args(0)]
 pub async fn join(ctx: &Context, message: &Message, _: Args) -> CommandResult {
-    let connection: &SqlitePool = SERVERS_DB
+    let connection: &AnyPool = SERVERS_DB
         .get()
         .expect("Connection should be established at this moment");
 
     let guild_id: GuildId = message
         .guild_id
         .expect("This should be called only on guilds");
-    let voice_channel_id: Option<ChannelId> = message
+    let Some(voice_channel_id) = message
         .guild(&ctx.cache)
         .expect("This should be called only on guilds")
         .voice_states
         .get(&message.author.id)
-        .and_then(|voice_state| voice_state.channel_id);
-    if voice_channel_id.is_none() {
-        return Ok(());
-    }
-    let voice_channel_id: ChannelId = voice_channel_id.expect("None case was handled");
+        .and_then(|voice_state| voice_state.channel_id)
+    else {
+        return Err(user_error(message, "music.not_in_voice").await);
+    };
 
-    if sqlx::query("SELECT * FROM music_bots WHERE guild_id = ? AND on_channel_id = ?")
-        .bind(guild_id.to_string())
-        .bind(voice_channel_id.to_string())
-        .fetch_optional(connection)
+    if music_bots::in_channel(connection, guild_id.into(), voice_channel_id.into())
         .await
         .expect("Query should be correct")
         .is_some()
     {
-        return Ok(());
+        return Err(user_error(message, "music.already_joined").await);
     }
-    let music_bot: Option<MusicBot> = sqlx::query_as::<_, MusicBot>(
-        "SELECT prefix FROM music_bots WHERE guild_id = ? AND on_channel_id IS NULL",
+    let music_bot: Option<MusicBot> = music_bots::free(connection, guild_id.into())
+        .await
+        .expect("Query should be correct");
+    let Some(MusicBot { prefix, .. }) = music_bot else {
+        return Err(user_error(message, "music.no_free_bot").await);
+    };
+    let Some(channel_id) = get_music_log_channel(guild_id).await else {
+        return Err(user_error(message, "music.no_log_channel").await);
+    };
+
+    channel_id
+        .say(&ctx.http, format!("{}join {}", prefix, voice_channel_id))
+        .await?;
+    let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+    let channel_name: String = voice_channel_id
+        .to_channel(&ctx.http)
+        .await
+        .expect("Channel exists on this guild")
+        .guild()
+        .expect("This channel is on guild")
+        .name;
+    reply::success(
+        ctx,
+        message,
+        &tr(language, "music.joined", &[("channel", &channel_name)]),
+    )
+    .await?;
+    music_bots::join(
+        connection,
+        guild_id.into(),
+        voice_channel_id.into(),
+        &prefix,
     )
-    .bind(guild_id.to_string())
-    .fetch_optional(connection)
     .await
-    .expect("Query should be correct");
-    if let Some(MusicBot {
-        prefix,
-        ..
-    }) = music_bot
-    {
-        if let Some(channel_id) = get_music_log_channel(guild_id).await {
-            channel_id
-                .say(&ctx.http, format!("{}join {}", prefix, voice_channel_id))
-                .await?;
-            message
-                .channel_id
-                .say(
-                    &ctx.http,
-                    format!(
-                        "➡️ 👍 Joined '{}' voice channel!!! ⬅️",
-                        voice_channel_id
-                            .to_channel(&ctx.http)
-                            .await
-                            .expect("Channel exists on this guild")
-                            .guild()
-                            .expect("This channel is on guild")
-                            .name
-                    ),
-                )
-                .await?;
-            sqlx::query(
-                "
-                INSERT INTO channels VALUES (?, ?);
-                UPDATE music_bots SET on_channel_id = ? WHERE guild_id = ? AND prefix = ?;
-            ",
-            )
-            .bind(voice_channel_id.to_string())
-            .bind(guild_id.to_string())
-            .bind(voice_channel_id.to_string())
-            .bind(guild_id.to_string())
-            .bind(prefix.clone())
-            .execute(connection)
-            .await
-            .expect("Query should be correct");
-            logger::log_discord(&ctx.http, guild_id, &format!("Called join on {}", prefix)).await;
-        }
-    }
+    .expect("Transaction should be committed");
+    logger::log_discord(
+        &ctx.http,
+        guild_id,
+        LogCategory::Music,
+        &format!("Called join on {}", prefix),
+    );
 
     Ok(())
 }
 #[command]
 #[num_args(0)]
 pub async fn leave(ctx: &Context, message: &Message, _: Args) -> CommandResult {
-    let connection: &SqlitePool = SERVERS_DB
+    let connection: &AnyPool = SERVERS_DB
         .get()
         .expect("Connection should be established at this moment");
 
     let guild_id: GuildId = message
         .guild_id
         .expect("This should be called only on guilds");
-    let voice_channel_id: Option<ChannelId> = message
+    let Some(voice_channel_id) = message
         .guild(&ctx.cache)
         .expect("This should be called only on guilds")
         .voice_states
         .get(&message.author.id)
-        .and_then(|voice_state| voice_state.channel_id);
-    if voice_channel_id.is_none() {
-        return Ok(());
-    }
-    let voice_channel_id: ChannelId = voice_channel_id.expect("None case was handled");
+        .and_then(|voice_state| voice_state.channel_id)
+    else {
+        return Err(user_error(message, "music.not_in_voice").await);
+    };
 
-    let music_bot: Option<MusicBot> = sqlx::query_as::<_, MusicBot>(
-        "SELECT prefix FROM music_bots WHERE guild_id = ? AND on_channel_id = ?",
-    )
-    .bind(guild_id.to_string())
-    .bind(voice_channel_id.to_string())
-    .fetch_optional(connection)
-    .await
-    .expect("Query should be correct");
+    let music_bot: Option<MusicBot> =
+        music_bots::in_channel(connection, guild_id.into(), voice_channel_id.into())
+            .await
+            .expect("Query should be correct");
+    let Some(MusicBot { prefix, .. }) = music_bot else {
+        return Err(user_error(message, "music.no_bot_in_channel").await);
+    };
+    let Some(channel_id) = get_music_log_channel(guild_id).await else {
+        return Err(user_error(message, "music.no_log_channel").await);
+    };
 
-    if let Some(MusicBot {
-        prefix,
-        ..
-    }) = music_bot
-    {
-        if let Some(channel_id) = get_music_log_channel(guild_id).await {
-            channel_id
-                .say(&ctx.http, format!("{}leave {}", prefix, voice_channel_id))
-                .await?;
-            message
-                .channel_id
-                .say(
-                    &ctx.http,
-                    format!(
-                        "➡️ 😔 Left '{}' voice channel :( ⬅️",
-                        voice_channel_id
-                            .to_channel(&ctx.http)
-                            .await
-                            .expect("Channel exists on this guild")
-                            .guild()
-                            .expect("This channel is on guild")
-                            .name
-                    ),
-                )
-                .await?;
-            sqlx::query("DELETE FROM channels WHERE discord_id = ?")
-                .bind(voice_channel_id.to_string())
-                .execute(connection)
-                .await
-                .expect("Query should be correct");
-            logger::log_discord(&ctx.http, guild_id, &format!("Called leave on {}", prefix)).await;
-        }
-    }
+    channel_id
+        .say(&ctx.http, format!("{}leave {}", prefix, voice_channel_id))
+        .await?;
+    let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+    let channel_name: String = voice_channel_id
+        .to_channel(&ctx.http)
+        .await
+        .expect("Channel exists on this guild")
+        .guild()
+        .expect("This channel is on guild")
+        .name;
+    reply::success(
+        ctx,
+        message,
+        &tr(language, "music.left", &[("channel", &channel_name)]),
+    )
+    .await?;
+    music_bots::leave(connection, voice_channel_id.into())
+        .await
+        .expect("Query should be correct");
+    // Settings that were referencing deleted channel are reset by foreign key.
+    cache::invalidate(guild_id);
+    logger::log_discord(
+        &ctx.http,
+        guild_id,
+        LogCategory::Music,
+        &format!("Called leave on {}", prefix),
+    );
 
     Ok(())
 }
@@ -277,59 +277,57 @@ macro_rules! no_args_command {
         #[command]
         #[num_args(0)]
         pub async fn $fullname(ctx: &Context, message: &Message, _: Args) -> CommandResult {
-            let connection: &SqlitePool = SERVERS_DB
+            let connection: &AnyPool = SERVERS_DB
                 .get()
                 .expect("Connection should be established at this moment");
 
             let guild_id: GuildId = message
                 .guild_id
                 .expect("This should be called only on guilds");
-            let voice_channel_id: Option<ChannelId> = message
+            let Some(voice_channel_id) = message
                 .guild(&ctx.cache)
                 .expect("This should be called only on guilds")
                 .voice_states
                 .get(&message.author.id)
-                .and_then(|voice_state| voice_state.channel_id);
-            if voice_channel_id.is_none() {
-                return Ok(());
-            }
+                .and_then(|voice_state| voice_state.channel_id)
+            else {
+                return Err(user_error(message, "music.not_in_voice").await);
+            };
 
-            let music_bot: Option<MusicBot> = sqlx::query_as::<_, MusicBot>(
-                "SELECT prefix FROM music_bots WHERE guild_id = ? AND on_channel_id = ?",
-            )
-            .bind(guild_id.to_string())
-            .bind(voice_channel_id.expect("None case was handled").to_string())
-            .fetch_optional(connection)
-            .await
-            .expect("Query should be correct");
+            let music_bot: Option<MusicBot> =
+                music_bots::in_channel(connection, guild_id.into(), voice_channel_id.into())
+                    .await
+                    .expect("Query should be correct");
+            let Some(MusicBot { prefix, .. }) = music_bot else {
+                return Err(user_error(message, "music.no_bot_in_channel").await);
+            };
+            let Some(channel_id) = get_music_log_channel(guild_id).await else {
+                return Err(user_error(message, "music.no_log_channel").await);
+            };
 
-            if let Some(MusicBot {
-                prefix,
-                ..
-            }) = music_bot
-            {
-                if let Some(channel_id) = get_music_log_channel(guild_id).await {
-                    channel_id
-                        .say(
-                            &ctx.http,
-                            format!(concat!("{}", stringify!($fullname)), prefix),
-                        )
-                        .await?;
-                    message
-                        .channel_id
-                        .say(
-                            &ctx.http,
-                            concat!("➡️ Called ", stringify!($fullname), " on current queue!!! ⬅️"),
-                        )
-                        .await?;
-                    logger::log_discord(
-                        &ctx.http,
-                        guild_id,
-                        &format!(concat!("Called ", stringify!($fullname), " on {}"), prefix),
-                    )
-                    .await;
-                }
-            }
+            channel_id
+                .say(
+                    &ctx.http,
+                    format!(concat!("{}", stringify!($fullname)), prefix),
+                )
+                .await?;
+            let language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+            reply::success(
+                ctx,
+                message,
+                &tr(
+                    language,
+                    "music.called",
+                    &[("command", &stringify!($fullname))],
+                ),
+            )
+            .await?;
+            logger::log_discord(
+                &ctx.http,
+                guild_id,
+                LogCategory::Music,
+                &format!(concat!("Called ", stringify!($fullname), " on {}"), prefix),
+            );
 
             Ok(())
         }
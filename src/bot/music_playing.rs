@@ -1,8 +1,19 @@
-use crate::logger;
+use crate::{
+    db::{
+        connections::SERVERS_DB,
+        models::{DbId, QueuedTrack},
+    },
+    logger, metrics,
+};
 use serenity::{
     client::Context,
-    model::{channel::Message, guild::Guild, id::ChannelId},
+    model::{
+        channel::Message,
+        guild::Guild,
+        id::{ChannelId, GuildId},
+    },
 };
+use sqlx::{Any, AnyPool, Transaction};
 use std::sync::Arc;
 
 pub enum MusicOrder {
@@ -57,6 +68,12 @@ pub async fn join(ctx: &Context, message: &Message, voice_channel_id: ChannelId)
         .clone();
 
     let _ = manager.join(guild.id, voice_channel_id).await;
+    if let Some(prefix) = crate::MUSIC_BOT_PREFIXES
+        .iter()
+        .find(|prefix| message.content.starts_with(**prefix))
+    {
+        restore_queue(&manager, guild.id, prefix).await;
+    }
 }
 pub async fn leave(ctx: &Context, message: &Message) {
     let guild = message
@@ -138,3 +155,92 @@ pub async fn stop(ctx: &Context, message: &Message) {
         queue.stop();
     }
 }
+
+/// Saves queue of music bot with given prefix, so that it is restored when bot joins guild next time.
+///
+pub async fn save_queue(manager: &songbird::Songbird, guild_id: GuildId, prefix: &str) {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    let urls: Vec<String> = match manager.get(guild_id) {
+        Some(handler_lock) => handler_lock
+            .lock()
+            .await
+            .queue()
+            .current_queue()
+            .iter()
+            .filter_map(|track| track.metadata().source_url.clone())
+            .collect::<Vec<String>>(),
+        None => return,
+    };
+    let _timer: metrics::Timer = metrics::Timer::query("music_queues.save");
+    let mut transaction: Transaction<'_, Any> = connection
+        .begin()
+        .await
+        .expect("Transaction should be started");
+    sqlx::query("DELETE FROM music_queues WHERE guild_id = $1 AND prefix = $2")
+        .bind(guild_id.db_id())
+        .bind(prefix.to_string())
+        .execute(&mut *transaction)
+        .await
+        .expect("Query should be correct");
+    for (position, url) in urls.iter().enumerate() {
+        sqlx::query("INSERT INTO music_queues VALUES ($1, $2, $3, $4)")
+            .bind(guild_id.db_id())
+            .bind(prefix.to_string())
+            .bind(position as i64)
+            .bind(url)
+            .execute(&mut *transaction)
+            .await
+            .expect("Query should be correct");
+    }
+    transaction
+        .commit()
+        .await
+        .expect("Transaction should be committed");
+    logger::log(
+        log::Level::Info,
+        &format!(
+            "Saved {} tracks of {} in '{}' guild",
+            urls.len(),
+            prefix.trim(),
+            guild_id
+        ),
+    );
+}
+
+/// Enqueues tracks that were saved by `save_queue` and forgets them.
+///
+async fn restore_queue(manager: &songbird::Songbird, guild_id: GuildId, prefix: &str) {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    let timer: metrics::Timer = metrics::Timer::query("music_queues.restore");
+    let mut tracks: Vec<QueuedTrack> = sqlx::query_as::<_, QueuedTrack>(
+        "DELETE FROM music_queues WHERE guild_id = $1 AND prefix = $2 RETURNING *",
+    )
+    .bind(guild_id.db_id())
+    .bind(prefix.to_string())
+    .fetch_all(connection)
+    .await
+    .expect("Query should be correct");
+    drop(timer);
+    if tracks.is_empty() {
+        return;
+    }
+
+    tracks.sort_by_key(|track| track.position);
+    if let Some(handler_lock) = manager.get(guild_id) {
+        let mut handler = handler_lock.lock().await;
+        for track in tracks {
+            match songbird::input::ytdl(track.url).await {
+                Ok(source) => handler.enqueue_source(source),
+                Err(why) => {
+                    logger::log(log::Level::Info, &format!("Error sourcing ffmpeg: {}", why))
+                }
+            }
+        }
+    }
+}
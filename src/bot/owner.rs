@@ -0,0 +1,463 @@
+//! `owner` module contains commands that bot owners use to operate bots.
+//!
+
+use crate::{
+    bot::{
+        locale::{self, tr},
+        prefix,
+        reply::{self, ReplyKind},
+        supervisor::{self, BotStatus},
+    },
+    db::{
+        self, cache,
+        connections::{Backend, SERVERS_BACKEND, SERVERS_DB},
+        models::{Guild, Language, Setting},
+    },
+    logger,
+};
+use serenity::{
+    client::Context,
+    framework::standard::{
+        macros::{command, group},
+        {Args, CommandResult},
+    },
+    model::{
+        channel::Message,
+        id::{ChannelId, GuildId},
+    },
+};
+use sqlx::{any::AnyRow, AnyConnection, AnyPool, Column, Connection, Executor, Row, ValueRef};
+use std::{
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+/// `Owner` struct is a group for `serenity` framework that contains commands for bot owners.
+///
+#[group]
+#[prefixes("owner")]
+#[owners_only]
+#[commands(status, reload_config, guilds, leave_guild, broadcast, sql)]
+pub struct Owner;
+
+/// Moment when bots were started.
+///
+pub static STARTED_AT: OnceLock<Instant> = OnceLock::new();
+
+/// Name of file that configuration is read from.
+///
+const CONFIG_FILE: &str = "SETTINGS.env";
+
+/// Maximal length of message that Discord accepts.
+///
+const MESSAGE_LIMIT: usize = 2000;
+
+/// Maximal number of rows that `sql` command shows.
+///
+const SQL_ROWS_LIMIT: usize = 20;
+
+/// Statements that `sql` command accepts.
+///
+const READONLY_STATEMENTS: [&str; 3] = ["select", "with", "explain"];
+
+/// Replies with text in as few info embeds as possible, splitting it by lines.
+///
+async fn reply_lines(ctx: &Context, message: &Message, text: &str) -> CommandResult {
+    let mut chunk: String = String::new();
+    for line in text.lines() {
+        if !chunk.is_empty() && chunk.len() + line.len() + 1 > MESSAGE_LIMIT {
+            let _ = reply::info(ctx, message, &chunk).await?;
+            chunk.clear();
+        }
+        chunk.push_str(line);
+        chunk.push('\n');
+    }
+    if !chunk.is_empty() {
+        let _ = reply::info(ctx, message, &chunk).await?;
+    }
+    Ok(())
+}
+
+/// Returns resident memory of process, if it can be found out.
+///
+fn memory() -> Option<String> {
+    let status: String = std::fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .map(|memory| memory.trim().to_string())
+}
+
+/// Formats duration as days, hours, minutes and seconds.
+///
+fn format_duration(duration: Duration) -> String {
+    let seconds: u64 = duration.as_secs();
+    format!(
+        "{}d {}h {}m {}s",
+        seconds / 86400,
+        seconds % 86400 / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+#[command]
+#[num_args(0)]
+pub async fn status(ctx: &Context, message: &Message, _: Args) -> CommandResult {
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    let (voice_connections,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM music_bots WHERE on_channel_id IS NOT NULL")
+            .fetch_one(connection)
+            .await
+            .expect("Query should be correct");
+    let bots: Vec<String> = supervisor::statuses()
+        .into_iter()
+        .map(|(name, BotStatus { state, restarts })| {
+            format!("{}: {} ({} restarts)", name, state.as_str(), restarts)
+        })
+        .collect::<Vec<String>>();
+    let reply: String = tr(
+        language,
+        "owner.status",
+        &[
+            (
+                "uptime",
+                &STARTED_AT.get().map_or("-".to_string(), |started| {
+                    format_duration(started.elapsed())
+                }),
+            ),
+            ("memory", &memory().unwrap_or("-".to_string())),
+            ("guilds", &ctx.cache.guild_count()),
+            ("voice", &voice_connections),
+            ("bots", &bots.join("\n")),
+        ],
+    );
+    let _ = reply::info(ctx, message, &reply).await?;
+
+    logger::log(log::Level::Info, "owner status was called");
+    Ok(())
+}
+
+/// Reads variables from configuration file and sets them in environment of process.
+///
+/// Returns number of variables that were read.
+///
+fn reload_env() -> Result<usize, String> {
+    let contents: String = std::fs::read_to_string(CONFIG_FILE)
+        .map_err(|error| format!("unable to read {}: {}", CONFIG_FILE, error))?;
+    let mut count: usize = 0;
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value): (&str, &str) = line
+            .trim_start_matches("export ")
+            .split_once('=')
+            .ok_or_else(|| format!("malformed line '{}'", line))?;
+        let value: &str = value.trim();
+        let value: &str = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .or_else(|| {
+                value
+                    .strip_prefix('\'')
+                    .and_then(|value| value.strip_suffix('\''))
+            })
+            .unwrap_or(value);
+        std::env::set_var(key.trim(), value);
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[command("reload-config")]
+#[num_args(0)]
+pub async fn reload_config(ctx: &Context, message: &Message, _: Args) -> CommandResult {
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+
+    let (kind, reply): (ReplyKind, String) = match reload_env() {
+        Ok(count) => {
+            cache::clear();
+            prefix::clear();
+            (
+                ReplyKind::Success,
+                tr(language, "owner.reloaded", &[("count", &count)]),
+            )
+        }
+        Err(error) => (
+            ReplyKind::Error,
+            tr(language, "owner.reload_failed", &[("error", &error)]),
+        ),
+    };
+    let _ = reply::send(ctx, message, kind, &reply).await?;
+
+    logger::log(log::Level::Info, "owner reload-config was called");
+    Ok(())
+}
+
+#[command]
+#[num_args(0)]
+pub async fn guilds(ctx: &Context, message: &Message, _: Args) -> CommandResult {
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    let registered: Vec<Guild> =
+        sqlx::query_as::<_, Guild>("SELECT * FROM guilds ORDER BY discord_id")
+            .fetch_all(connection)
+            .await
+            .expect("Query should be correct");
+    let mut lines: Vec<String> = Vec::new();
+    for guild in registered {
+        let guild_id: GuildId = guild.discord_id.into();
+        let setting: Setting = match cache::get(guild_id).await {
+            Some(setting) => setting,
+            None => continue,
+        };
+        let set = |id: Option<u64>| if id.is_some() { "+" } else { "-" };
+        lines.push(format!(
+            "`{}` {}: prefix `{}`, {}, log {}, member role {}",
+            guild_id,
+            guild_id.name(&ctx.cache).unwrap_or_default(),
+            setting.prefix.as_deref().unwrap_or(crate::MAIN_BOT_PREFIX),
+            setting
+                .language
+                .as_deref()
+                .unwrap_or(Language::default().as_str()),
+            set(setting.log_channel_id.0),
+            set(setting.member_role_id.0),
+        ));
+    }
+    let reply: String = tr(
+        language,
+        "owner.guilds",
+        &[("count", &lines.len()), ("guilds", &lines.join("\n"))],
+    );
+    reply_lines(ctx, message, &reply).await?;
+
+    logger::log(log::Level::Info, "owner guilds was called");
+    Ok(())
+}
+
+#[command("leave-guild")]
+#[usage("<guild id>")]
+#[example("123456789012345678")]
+#[num_args(1)]
+pub async fn leave_guild(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+
+    let guild_id: GuildId = match args.single::<u64>() {
+        Ok(guild_id) => GuildId(guild_id),
+        Err(_) => return Err(reply::user_error(tr(language, "owner.invalid_guild", &[]))),
+    };
+    let (kind, reply): (ReplyKind, String) = match guild_id.leave(&ctx.http).await {
+        Ok(()) => (
+            ReplyKind::Success,
+            tr(language, "owner.left", &[("guild", &guild_id)]),
+        ),
+        Err(error) => (
+            ReplyKind::Error,
+            tr(
+                language,
+                "owner.leave_failed",
+                &[("guild", &guild_id), ("error", &error)],
+            ),
+        ),
+    };
+    let _ = reply::send(ctx, message, kind, &reply).await?;
+
+    logger::log(
+        log::Level::Info,
+        &format!("owner leave-guild was called for '{}' guild", guild_id),
+    );
+    Ok(())
+}
+
+#[command]
+#[usage("<message>")]
+#[example("Bots will be restarted in 5 minutes")]
+#[min_args(1)]
+pub async fn broadcast(ctx: &Context, message: &Message, args: Args) -> CommandResult {
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    let settings: Vec<Setting> =
+        sqlx::query_as::<_, Setting>("SELECT * FROM settings WHERE log_channel_id IS NOT NULL")
+            .fetch_all(connection)
+            .await
+            .expect("Query should be correct");
+    let mut sent: usize = 0;
+    let mut failed: usize = 0;
+    for channel_id in settings
+        .iter()
+        .filter_map(|setting| setting.log_channel_id.0)
+    {
+        let channel_id: ChannelId = ChannelId(channel_id);
+        match reply::announce(ctx, channel_id, ReplyKind::Info, args.rest()).await {
+            Ok(_) => sent += 1,
+            Err(_) => failed += 1,
+        }
+    }
+    let reply: String = tr(
+        language,
+        "owner.broadcasted",
+        &[("sent", &sent), ("failed", &failed)],
+    );
+    let kind: ReplyKind = if failed == 0 {
+        ReplyKind::Success
+    } else {
+        ReplyKind::Warning
+    };
+    let _ = reply::send(ctx, message, kind, &reply).await?;
+
+    logger::log(log::Level::Info, "owner broadcast was called");
+    Ok(())
+}
+
+/// Opens connection to servers database that is not able to change it.
+///
+async fn readonly_connection() -> Result<AnyConnection, String> {
+    let url: String = db::servers_database_url();
+    match SERVERS_BACKEND.get() {
+        Some(Backend::Sqlite) => {
+            let separator: char = if url.contains('?') { '&' } else { '?' };
+            AnyConnection::connect(&format!("{}{}mode=ro", url, separator))
+                .await
+                .map_err(|error| error.to_string())
+        }
+        Some(Backend::Postgres) => {
+            let mut connection: AnyConnection = AnyConnection::connect(&url)
+                .await
+                .map_err(|error| error.to_string())?;
+            let _ = connection
+                .execute("SET SESSION CHARACTERISTICS AS TRANSACTION READ ONLY")
+                .await
+                .map_err(|error| error.to_string())?;
+            Ok(connection)
+        }
+        None => Err("database is not connected".to_string()),
+    }
+}
+
+/// Formats value of column in row.
+///
+fn format_value(row: &AnyRow, index: usize) -> String {
+    if row.try_get_raw(index).map_or(true, |value| value.is_null()) {
+        return "NULL".to_string();
+    }
+    if let Ok(value) = row.try_get::<i64, _>(index) {
+        return value.to_string();
+    }
+    if let Ok(value) = row.try_get::<f64, _>(index) {
+        return value.to_string();
+    }
+    if let Ok(value) = row.try_get::<String, _>(index) {
+        return value;
+    }
+    if let Ok(value) = row.try_get::<bool, _>(index) {
+        return value.to_string();
+    }
+    if let Ok(value) = row.try_get::<Vec<u8>, _>(index) {
+        return format!("<{} bytes>", value.len());
+    }
+    "?".to_string()
+}
+
+/// Executes read-only query and formats its result as a table.
+///
+async fn run_query(query: &str) -> Result<String, String> {
+    let mut connection: AnyConnection = readonly_connection().await?;
+    let rows: Vec<AnyRow> = sqlx::query(query)
+        .fetch_all(&mut connection)
+        .await
+        .map_err(|error| error.to_string())?;
+    let _ = connection.close().await;
+
+    let mut lines: Vec<String> = Vec::new();
+    if let Some(row) = rows.first() {
+        lines.push(
+            row.columns()
+                .iter()
+                .map(|column| column.name().to_string())
+                .collect::<Vec<String>>()
+                .join(" | "),
+        );
+    }
+    for row in rows.iter().take(SQL_ROWS_LIMIT) {
+        lines.push(
+            (0..row.len())
+                .map(|index| format_value(row, index))
+                .collect::<Vec<String>>()
+                .join(" | "),
+        );
+    }
+    if rows.len() > SQL_ROWS_LIMIT {
+        lines.push(format!("... {} more rows", rows.len() - SQL_ROWS_LIMIT));
+    }
+    Ok(lines.join("\n"))
+}
+
+#[command]
+#[usage("<query>")]
+#[example("SELECT COUNT(*) FROM guilds")]
+#[min_args(1)]
+pub async fn sql(ctx: &Context, message: &Message, args: Args) -> CommandResult {
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let query: &str = args
+        .rest()
+        .trim()
+        .trim_start_matches("```sql")
+        .trim_matches('`')
+        .trim();
+
+    let statement: String = query
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    logger::log_with(
+        log::Level::Info,
+        "owner sql was called",
+        &[("user_id", &message.author.id), ("query", &query)],
+    );
+
+    if !READONLY_STATEMENTS.contains(&statement.as_str()) || query.contains(';') {
+        return Err(reply::user_error(tr(language, "owner.sql_rejected", &[])));
+    }
+    match run_query(query).await {
+        Ok(table) if table.is_empty() => {
+            let _ = reply::info(ctx, message, &tr(language, "owner.sql_empty", &[])).await?;
+        }
+        Ok(table) => {
+            let mut table: String = table;
+            if table.len() > MESSAGE_LIMIT - 8 {
+                let mut end: usize = MESSAGE_LIMIT - 12;
+                while !table.is_char_boundary(end) {
+                    end -= 1;
+                }
+                table.truncate(end);
+                table.push_str("\n...");
+            }
+            let _ = message
+                .channel_id
+                .say(&ctx.http, format!("```\n{}\n```", table))
+                .await?;
+        }
+        Err(error) => {
+            let _ = reply::error(
+                ctx,
+                message,
+                &tr(language, "owner.sql_failed", &[("error", &error)]),
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,184 @@
+//! `prefix` module implements per-guild command prefixes and command aliases
+//! that are resolved through `serenity` framework hooks.
+//!
+
+use crate::db::{aliases, cache, connections::SERVERS_DB};
+use serenity::{
+    client::Context,
+    framework::{
+        standard::{macros::hook, CommandGroup},
+        Framework, StandardFramework,
+    },
+    model::{channel::Message, id::GuildId},
+};
+use sqlx::AnyPool;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, OnceLock},
+};
+
+/// Maximal length of guild prefix.
+///
+pub const MAX_PREFIX_LENGTH: usize = 8;
+
+/// Prefix that is used when guild has not set its own one.
+///
+static DEFAULT_PREFIX: OnceLock<String> = OnceLock::new();
+
+/// Framework that aliased commands are dispatched to.
+///
+static FRAMEWORK: OnceLock<Arc<StandardFramework>> = OnceLock::new();
+
+/// Words that commands of framework start with: names of commands and prefixes of groups.
+///
+static COMMAND_NAMES: OnceLock<HashSet<&'static str>> = OnceLock::new();
+
+/// Cached command aliases of guilds.
+///
+static ALIASES: OnceLock<Mutex<HashMap<GuildId, HashMap<String, String>>>> = OnceLock::new();
+
+/// Sets default prefix and framework that aliased commands are dispatched to,
+/// along with its groups and names of help command, so that aliases can be checked.
+///
+pub fn init(
+    default_prefix: &str,
+    framework: Arc<StandardFramework>,
+    groups: &[&'static CommandGroup],
+    help_names: &[&'static str],
+) {
+    let _ = DEFAULT_PREFIX.set(default_prefix.to_string());
+    let _ = FRAMEWORK.set(framework);
+    let _ = COMMAND_NAMES.set(
+        groups
+            .iter()
+            .flat_map(|group| match group.options.prefixes {
+                [] => group
+                    .options
+                    .commands
+                    .iter()
+                    .flat_map(|command| command.options.names.iter())
+                    .copied()
+                    .collect::<Vec<&'static str>>(),
+                prefixes => prefixes.to_vec(),
+            })
+            .chain(help_names.iter().copied())
+            .collect::<HashSet<&'static str>>(),
+    );
+}
+
+/// Returns whether word is a name of command (or prefix of command group) of framework.
+///
+pub fn is_command(name: &str) -> bool {
+    COMMAND_NAMES
+        .get()
+        .is_some_and(|names| names.contains(name))
+}
+
+/// Returns prefix of guild (or default prefix if guild is not given).
+///
+pub async fn get_prefix(guild_id: Option<GuildId>) -> String {
+    let default_prefix: String = DEFAULT_PREFIX
+        .get()
+        .cloned()
+        .unwrap_or_else(|| crate::MAIN_BOT_PREFIX.to_string());
+    match guild_id {
+        Some(guild_id) => cache::get(guild_id)
+            .await
+            .and_then(|setting| setting.prefix)
+            .unwrap_or(default_prefix),
+        None => default_prefix,
+    }
+}
+
+/// Returns command aliases of guild.
+///
+pub async fn get_aliases(guild_id: GuildId) -> HashMap<String, String> {
+    if let Some(aliases) = ALIASES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned")
+        .get(&guild_id)
+    {
+        return aliases.clone();
+    }
+
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+    let aliases: HashMap<String, String> = aliases::list(connection, guild_id.into())
+        .await
+        .expect("Query should be correct")
+        .into_iter()
+        .map(|alias| (alias.alias, alias.command))
+        .collect::<HashMap<String, String>>();
+    let _ = ALIASES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned")
+        .insert(guild_id, aliases.clone());
+    aliases
+}
+
+/// Drops cached aliases of guild so they will be reloaded from database.
+///
+pub fn invalidate(guild_id: GuildId) {
+    let _ = ALIASES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned")
+        .remove(&guild_id);
+}
+
+/// Drops cached aliases of all guilds.
+///
+pub fn clear() {
+    ALIASES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned")
+        .clear();
+}
+
+/// Resolves prefix of guild that message was sent in.
+///
+#[hook]
+pub async fn dynamic_prefix(_: &Context, message: &Message) -> Option<String> {
+    Some(get_prefix(message.guild_id).await)
+}
+
+/// Dispatches aliased command if unrecognised command is an alias.
+///
+/// Aliases that do not lead to a command of framework are ignored, since dispatching them
+/// would call this hook again (and `before` hook, which limits commands, is not called here).
+///
+#[hook]
+pub async fn unrecognised_command(ctx: &Context, message: &Message, name: &str) {
+    let guild_id: GuildId = match message.guild_id {
+        Some(guild_id) => guild_id,
+        None => return,
+    };
+    let mut aliases: HashMap<String, String> = get_aliases(guild_id).await;
+    let command: String = match aliases.remove(name) {
+        Some(command) => command,
+        None => return,
+    };
+    let command_name: &str = command.split_whitespace().next().unwrap_or_default();
+    if command_name == name || aliases.contains_key(command_name) || !is_command(command_name) {
+        return;
+    }
+    let framework: Arc<StandardFramework> = match FRAMEWORK.get() {
+        Some(framework) => Arc::clone(framework),
+        None => return,
+    };
+
+    let prefix: String = get_prefix(Some(guild_id)).await;
+    let rest: &str = message
+        .content
+        .strip_prefix(prefix.as_str())
+        .map(|content| content.trim_start())
+        .and_then(|content| content.strip_prefix(name))
+        .unwrap_or_default();
+    let mut aliased: Message = message.clone();
+    aliased.content = format!("{}{}{}", prefix, command, rest);
+    framework.dispatch(ctx.clone(), aliased).await;
+}
@@ -0,0 +1,503 @@
+//! `raid` module implements join-rate monitoring and lockdown mode that protects guilds from raids,
+//! and commands that are used to configure it.
+//!
+
+use crate::{
+    bot::{
+        events::welcome_message,
+        locale::{self, tr},
+        prefix,
+        reply::{self, ReplyKind},
+    },
+    db::{
+        cache,
+        connections::SERVERS_DB,
+        models::{DbId, Id, Language, RaidSetting, UnregisteredMember},
+    },
+    logger, metrics,
+};
+use serenity::{
+    builder::CreateComponents,
+    client::Context,
+    framework::standard::{
+        macros::{command, group},
+        {Args, CommandResult},
+    },
+    model::{
+        application::component::ButtonStyle,
+        channel::Message,
+        guild::Member,
+        id::{ChannelId, GuildId, UserId},
+        Timestamp,
+    },
+};
+use sqlx::AnyPool;
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+/// `Raid` struct is a group for `serenity` framework that contains raid protection commands.
+///
+#[group]
+#[prefixes("raid")]
+#[required_permissions("ADMINISTRATOR")]
+#[only_in(guilds)]
+#[default_command(status)]
+#[commands(threshold, account_age, quarantine_role, lockdown, lift, status)]
+pub struct Raid;
+
+/// Custom id of button that lifts lockdown.
+///
+pub const LIFT_LOCKDOWN_BUTTON: &str = "lift_lockdown";
+
+/// Recent joins of every guild.
+///
+static JOINS: OnceLock<Mutex<HashMap<GuildId, Vec<Instant>>>> = OnceLock::new();
+
+/// `JoinVerdict` enum represents how new member should be processed.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum JoinVerdict {
+    /// Member should be registered as usual.
+    ///
+    Allow,
+    /// Registration of member should be paused until lockdown is lifted.
+    ///
+    Pause,
+    /// Member was quarantined and should not be registered.
+    ///
+    Quarantine,
+}
+
+async fn get_raid_setting(guild_id: GuildId) -> Option<RaidSetting> {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+    let _timer: metrics::Timer = metrics::Timer::query("raid_settings.get");
+
+    sqlx::query_as::<_, RaidSetting>("SELECT * FROM raid_settings WHERE guild_id = $1")
+        .bind(guild_id.db_id())
+        .fetch_optional(connection)
+        .await
+        .expect("Query should be correct")
+}
+
+async fn get_moderation_channel(guild_id: GuildId) -> Option<ChannelId> {
+    cache::get(guild_id)
+        .await
+        .and_then(|setting| setting.moderation_channel_id.0)
+        .map(ChannelId)
+}
+
+/// Builds button that lifts lockdown.
+///
+fn lift_button(components: &mut CreateComponents, language: Language) -> &mut CreateComponents {
+    components.create_action_row(|row| {
+        row.create_button(|button| {
+            button
+                .custom_id(LIFT_LOCKDOWN_BUTTON)
+                .label(tr(language, "raid.lift_button", &[]))
+                .style(ButtonStyle::Danger)
+        })
+    })
+}
+
+/// Enables lockdown and alerts moderators with given reason (in language of guild).
+///
+/// Returns `false` if lockdown was already enabled.
+///
+async fn enable_lockdown(ctx: &Context, guild_id: GuildId, reason: &str) -> bool {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    let is_enabled: bool = metrics::timed(
+        "raid_settings.lockdown",
+        sqlx::query(
+            "
+        INSERT INTO raid_settings (guild_id, lockdown) VALUES ($1, 1)
+        ON CONFLICT (guild_id) DO UPDATE SET lockdown = 1 WHERE raid_settings.lockdown = 0
+    ",
+        )
+        .bind(guild_id.db_id())
+        .execute(connection),
+    )
+    .await
+    .expect("Query should be correct")
+    .rows_affected()
+        > 0;
+    if !is_enabled {
+        return false;
+    }
+
+    if let Some(moderation_channel_id) = get_moderation_channel(guild_id).await {
+        let language: Language = locale::guild_language(guild_id).await;
+        let prefix: String = prefix::get_prefix(Some(guild_id)).await;
+        if let Err(error) = moderation_channel_id
+            .send_message(&ctx.http, |m| {
+                m.content(tr(
+                    language,
+                    "raid.lockdown_alert",
+                    &[("reason", &reason), ("prefix", &prefix)],
+                ))
+                .components(|c| lift_button(c, language))
+            })
+            .await
+        {
+            logger::log(
+                log::Level::Warn,
+                &format!(
+                    "An error occured while trying to alert about lockdown: {}",
+                    error
+                ),
+            );
+        }
+    }
+    logger::log(
+        log::Level::Warn,
+        &format!("Enabled lockdown on '{}' guild: {}", guild_id, reason),
+    );
+    true
+}
+
+/// Lifts lockdown, releases quarantined members, resumes paused registrations
+/// and notifies moderators.
+///
+/// Returns `false` if lockdown was not enabled.
+///
+pub async fn lift_lockdown(ctx: &Context, guild_id: GuildId, lifted_by: UserId) -> bool {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    if metrics::timed(
+        "raid_settings.lift",
+        sqlx::query("UPDATE raid_settings SET lockdown = 0 WHERE guild_id = $1 AND lockdown = 1")
+            .bind(guild_id.db_id())
+            .execute(connection),
+    )
+    .await
+    .expect("Query should be correct")
+    .rows_affected()
+        == 0
+    {
+        return false;
+    }
+    if let Some(joins) = JOINS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned")
+        .get_mut(&guild_id)
+    {
+        joins.clear();
+    }
+
+    let paused_members: Vec<UnregisteredMember> = metrics::timed(
+        "unregistered_members.get_paused",
+        sqlx::query_as::<_, UnregisteredMember>(
+            "SELECT * FROM unregistered_members WHERE guild_id = $1 AND is_paused = 1",
+        )
+        .bind(guild_id.db_id())
+        .fetch_all(connection),
+    )
+    .await
+    .expect("Query should be correct");
+    let quarantine_role_id: Option<u64> = get_raid_setting(guild_id)
+        .await
+        .and_then(|raid_setting| raid_setting.quarantine_role_id.0);
+    let guild_name: String = guild_id.name(&ctx.cache).unwrap_or_default();
+    for member in paused_members {
+        let user_id: UserId = UserId::from(member.discord_id);
+        if let (true, Some(quarantine_role_id)) = (member.is_quarantined.0, quarantine_role_id) {
+            if let Err(error) = ctx
+                .http
+                .remove_member_role(
+                    guild_id.0,
+                    user_id.0,
+                    quarantine_role_id,
+                    Some("Lockdown was lifted"),
+                )
+                .await
+            {
+                logger::log(
+                    log::Level::Warn,
+                    &format!(
+                        "An error occured while trying to release member from quarantine: {}",
+                        error
+                    ),
+                );
+            }
+        }
+        let language: Language = locale::user_language(Some(guild_id), user_id).await;
+        if let Ok(user) = user_id.to_user(ctx).await {
+            let _ = user
+                .dm(ctx, |m| m.content(welcome_message(language, &guild_name)))
+                .await;
+        }
+    }
+    metrics::timed(
+        "unregistered_members.release",
+        sqlx::query(
+            "UPDATE unregistered_members SET is_paused = 0, is_quarantined = 0 WHERE guild_id = $1",
+        )
+        .bind(guild_id.db_id())
+        .execute(connection),
+    )
+    .await
+    .expect("Query should be correct");
+
+    if let Some(moderation_channel_id) = get_moderation_channel(guild_id).await {
+        let language: Language = locale::guild_language(guild_id).await;
+        let _ = reply::announce(
+            ctx,
+            moderation_channel_id,
+            ReplyKind::Success,
+            &tr(language, "raid.lifted_by", &[("user", &lifted_by)]),
+        )
+        .await;
+    }
+    logger::log(
+        log::Level::Info,
+        &format!("Lifted lockdown on '{}' guild", guild_id),
+    );
+    true
+}
+
+/// Registers join of new member and decides how it should be processed.
+///
+pub async fn on_member_join(ctx: &Context, member: &Member) -> JoinVerdict {
+    let raid_setting: RaidSetting = match get_raid_setting(member.guild_id).await {
+        Some(raid_setting) => raid_setting,
+        None => return JoinVerdict::Allow,
+    };
+
+    let mut is_lockdown: bool = raid_setting.lockdown.0;
+    if let (Some(threshold), Some(period)) = (raid_setting.join_threshold, raid_setting.join_period)
+    {
+        let period: Duration = Duration::from_secs(period.max(0) as u64);
+        let joins_count: usize = {
+            let mut joins = JOINS
+                .get_or_init(|| Mutex::new(HashMap::new()))
+                .lock()
+                .expect("Lock should not be poisoned");
+            let guild_joins: &mut Vec<Instant> = joins.entry(member.guild_id).or_default();
+            guild_joins.retain(|joined_at| joined_at.elapsed() <= period);
+            guild_joins.push(Instant::now());
+            guild_joins.len()
+        };
+        if !is_lockdown && joins_count as i64 > threshold {
+            let language: Language = locale::guild_language(member.guild_id).await;
+            let _ = enable_lockdown(
+                ctx,
+                member.guild_id,
+                &tr(
+                    language,
+                    "raid.reason_joins",
+                    &[("count", &joins_count), ("seconds", &period.as_secs())],
+                ),
+            )
+            .await;
+            is_lockdown = true;
+        }
+    }
+    if !is_lockdown {
+        return JoinVerdict::Allow;
+    }
+
+    let account_age: i64 = (Timestamp::now().unix_timestamp()
+        - member.user.id.created_at().unix_timestamp())
+        / (24 * 60 * 60);
+    if account_age >= raid_setting.min_account_age.unwrap_or(0) {
+        return JoinVerdict::Pause;
+    }
+    if let Some(quarantine_role_id) = raid_setting.quarantine_role_id.0 {
+        if let Err(error) = ctx
+            .http
+            .add_member_role(
+                member.guild_id.0,
+                member.user.id.0,
+                quarantine_role_id,
+                Some("Quarantined during lockdown"),
+            )
+            .await
+        {
+            logger::log(
+                log::Level::Warn,
+                &format!(
+                    "An error occured while trying to quarantine member: {}",
+                    error
+                ),
+            );
+        }
+    }
+    if let Some(moderation_channel_id) = get_moderation_channel(member.guild_id).await {
+        let language: Language = locale::guild_language(member.guild_id).await;
+        let _ = reply::announce(
+            ctx,
+            moderation_channel_id,
+            ReplyKind::Warning,
+            &tr(
+                language,
+                "raid.quarantined",
+                &[("user", &member.user.id), ("count", &account_age)],
+            ),
+        )
+        .await;
+    }
+    JoinVerdict::Quarantine
+}
+
+macro_rules! setup_raid_setting {
+    ($(#[$meta:meta])* $fullname:ident, $args:literal, $query:literal, $($name:ident: $type:ty),+) => {
+        #[command]
+        $(#[$meta])*
+        #[num_args($args)]
+        pub async fn $fullname(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+            let connection: &AnyPool = SERVERS_DB
+                .get()
+                .expect("Connection should be established at this moment");
+
+            let guild_id: GuildId = message
+                .guild_id
+                .expect("This should be called only on guilds");
+            $(let $name: $type = args.single::<$type>()?;)+
+
+            metrics::timed(
+                "raid_settings.insert",
+                sqlx::query("INSERT INTO raid_settings (guild_id) VALUES ($1) ON CONFLICT DO NOTHING")
+                    .bind(guild_id.db_id())
+                    .execute(connection),
+            )
+                .await
+                .expect("Query should be correct");
+            metrics::timed(
+                "raid_settings.update",
+                sqlx::query(concat!("UPDATE raid_settings SET ", $query, " WHERE guild_id = $1"))
+                    .bind(guild_id.db_id())
+                    $(.bind(i64::from($name)))+
+                    .execute(connection),
+            )
+                .await
+                .expect("Query should be correct");
+            let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+            reply::success(ctx, message, &tr(language, "raid.setting_updated", &[("setting", &stringify!($fullname))])).await?;
+
+            logger::log(log::Level::Info, concat!("raid ", stringify!($fullname), " was called"));
+            Ok(())
+        }
+    };
+}
+setup_raid_setting!(
+    #[usage("<joins> <seconds>")]
+    #[example("10 30")]
+    threshold, 2, "join_threshold = $2, join_period = $3", joins: u32, period: u32
+);
+setup_raid_setting!(
+    #[usage("<days>")]
+    #[example("7")]
+    account_age, 1, "min_account_age = $2", days: u32
+);
+setup_raid_setting!(
+    #[usage("<role>")]
+    #[example("@Quarantine")]
+    quarantine_role, 1, "quarantine_role_id = $2", role_id: Id
+);
+
+#[command]
+#[num_args(0)]
+pub async fn lockdown(ctx: &Context, message: &Message, _: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+
+    let guild_language: Language = locale::guild_language(guild_id).await;
+    let reason: String = tr(
+        guild_language,
+        "raid.reason_manual",
+        &[("user", &message.author.id)],
+    );
+    if !enable_lockdown(ctx, guild_id, &reason).await {
+        return Err(reply::user_error(tr(
+            language,
+            "raid.already_locked_down",
+            &[],
+        )));
+    }
+    reply::success(ctx, message, &tr(language, "raid.lockdown_enabled", &[])).await?;
+
+    Ok(())
+}
+
+#[command]
+#[num_args(0)]
+pub async fn lift(ctx: &Context, message: &Message, _: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+
+    if !lift_lockdown(ctx, guild_id, message.author.id).await {
+        return Err(reply::user_error(tr(language, "raid.not_locked_down", &[])));
+    }
+    reply::success(ctx, message, &tr(language, "events.lockdown_lifted", &[])).await?;
+
+    Ok(())
+}
+
+#[command]
+#[num_args(0)]
+pub async fn status(ctx: &Context, message: &Message, _: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+
+    let reply: String = match get_raid_setting(guild_id).await {
+        Some(raid_setting) => tr(
+            language,
+            "raid.status",
+            &[
+                (
+                    "lockdown",
+                    &tr(
+                        language,
+                        if raid_setting.lockdown.0 {
+                            "raid.enabled"
+                        } else {
+                            "raid.disabled"
+                        },
+                        &[],
+                    ),
+                ),
+                (
+                    "threshold",
+                    &match (raid_setting.join_threshold, raid_setting.join_period) {
+                        (Some(joins), Some(period)) => tr(
+                            language,
+                            "raid.threshold",
+                            &[("joins", &joins), ("seconds", &period)],
+                        ),
+                        _ => tr(language, "raid.not_set", &[]),
+                    },
+                ),
+                ("days", &raid_setting.min_account_age.unwrap_or(0)),
+                (
+                    "role",
+                    &raid_setting
+                        .quarantine_role_id
+                        .0
+                        .map_or(tr(language, "raid.not_set", &[]), |role_id| {
+                            format!("<@&{}>", role_id)
+                        }),
+                ),
+            ],
+        ),
+        None => tr(language, "raid.not_configured", &[]),
+    };
+    reply::info(ctx, message, &reply).await?;
+
+    Ok(())
+}
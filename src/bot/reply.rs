@@ -0,0 +1,264 @@
+//! `reply` module implements replies to commands, so that all of them look the same,
+//! and hooks that tell users why their command did not work.
+//!
+//! Every reply is an embed which colour depends on its kind. Commands report mistakes of users
+//! by returning [`UserError`], which is shown as warning, while other errors are logged and shown
+//! as generic error, so that no command fails silently.
+//!
+
+use crate::{
+    bot::{
+        locale::{self, tr},
+        prefix,
+    },
+    db::models::Language,
+    logger, metrics,
+};
+use serenity::{
+    client::Context,
+    framework::standard::{macros::hook, CommandError, CommandResult, DispatchError, Reason},
+    model::{channel::Message, id::ChannelId},
+    utils::Colour,
+    Result,
+};
+use std::{error::Error, fmt};
+
+/// Maximal number of characters in description of embed.
+///
+const DESCRIPTION_LIMIT: usize = 4096;
+
+/// `ReplyKind` enum represents kinds of replies.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReplyKind {
+    /// Command was done.
+    ///
+    Success,
+    /// Command shows some information.
+    ///
+    Info,
+    /// Command was not done because of user's mistake.
+    ///
+    Warning,
+    /// Command failed.
+    ///
+    Error,
+}
+impl ReplyKind {
+    /// Returns colour of embed of reply.
+    ///
+    pub fn colour(&self) -> Colour {
+        match self {
+            ReplyKind::Success => Colour::DARK_GREEN,
+            ReplyKind::Info => Colour::BLUE,
+            ReplyKind::Warning => Colour::ORANGE,
+            ReplyKind::Error => Colour::RED,
+        }
+    }
+}
+
+/// `UserError` struct represents error that was caused by user, so its text is shown to them as is.
+///
+#[derive(Debug)]
+pub struct UserError(pub String);
+impl fmt::Display for UserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+impl Error for UserError {}
+
+/// Builds command error that is shown to user as warning with given text.
+///
+pub fn user_error(text: String) -> CommandError {
+    Box::new(UserError(text))
+}
+
+/// Truncates text that does not fit into embed, so that discord does not reject it.
+///
+fn fit(text: &str) -> String {
+    if text.chars().count() <= DESCRIPTION_LIMIT {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(DESCRIPTION_LIMIT - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Replies to message with embed of given kind.
+///
+pub async fn send(
+    ctx: &Context,
+    message: &Message,
+    kind: ReplyKind,
+    text: &str,
+) -> Result<Message> {
+    message
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.reference_message(message)
+                .allowed_mentions(|a| a.replied_user(false))
+                .embed(|e| e.colour(kind.colour()).description(fit(text)))
+        })
+        .await
+}
+
+/// Sends embed of given kind to channel without replying to any message,
+/// e.g. to alert moderators.
+///
+pub async fn announce(
+    ctx: &Context,
+    channel_id: ChannelId,
+    kind: ReplyKind,
+    text: &str,
+) -> Result<Message> {
+    channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| e.colour(kind.colour()).description(fit(text)))
+        })
+        .await
+}
+
+/// Replies to message with success embed.
+///
+pub async fn success(ctx: &Context, message: &Message, text: &str) -> Result<Message> {
+    send(ctx, message, ReplyKind::Success, text).await
+}
+
+/// Replies to message with info embed.
+///
+pub async fn info(ctx: &Context, message: &Message, text: &str) -> Result<Message> {
+    send(ctx, message, ReplyKind::Info, text).await
+}
+
+/// Replies to message with warning embed.
+///
+pub async fn warning(ctx: &Context, message: &Message, text: &str) -> Result<Message> {
+    send(ctx, message, ReplyKind::Warning, text).await
+}
+
+/// Replies to message with error embed.
+///
+pub async fn error(ctx: &Context, message: &Message, text: &str) -> Result<Message> {
+    send(ctx, message, ReplyKind::Error, text).await
+}
+
+/// Hook that records metrics of commands and tells users about errors that commands returned.
+///
+#[hook]
+pub async fn after(ctx: &Context, message: &Message, command_name: &str, result: CommandResult) {
+    metrics::record_command(command_name, result.is_err());
+    let error: CommandError = match result {
+        Ok(()) => return,
+        Err(error) => error,
+    };
+
+    let reply: Result<Message> = match error.downcast_ref::<UserError>() {
+        Some(UserError(text)) => warning(ctx, message, text).await,
+        None => {
+            let guild_id: String = message
+                .guild_id
+                .map_or("none".to_string(), |guild_id| guild_id.to_string());
+            logger::log_with(
+                log::Level::Warn,
+                &format!("Command failed: {}", error),
+                &[
+                    ("guild_id", &guild_id),
+                    ("user_id", &message.author.id),
+                    ("command", &command_name),
+                ],
+            );
+            let language: Language =
+                locale::user_language(message.guild_id, message.author.id).await;
+            self::error(
+                ctx,
+                message,
+                &tr(
+                    language,
+                    "reply.command_failed",
+                    &[("command", &command_name)],
+                ),
+            )
+            .await
+        }
+    };
+    if let Err(error) = reply {
+        logger::log(
+            log::Level::Warn,
+            &format!(
+                "An error occured while trying to reply about failed command: {}",
+                error
+            ),
+        );
+    }
+}
+
+/// Hook that tells users why their command was not dispatched.
+///
+/// Errors that users can not fix (blocked users, bots, etc.) are ignored silently.
+///
+#[hook]
+pub async fn dispatch_error(
+    ctx: &Context,
+    message: &Message,
+    dispatch_error: DispatchError,
+    command_name: &str,
+) {
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let (kind, text): (ReplyKind, String) = match dispatch_error {
+        DispatchError::CheckFailed(_, Reason::User(text))
+        | DispatchError::CheckFailed(_, Reason::UserAndLog { user: text, .. }) => {
+            (ReplyKind::Warning, text)
+        }
+        DispatchError::NotEnoughArguments { .. } | DispatchError::TooManyArguments { .. } => {
+            let prefix: String = prefix::get_prefix(message.guild_id).await;
+            (
+                ReplyKind::Warning,
+                tr(
+                    language,
+                    "reply.wrong_arguments",
+                    &[("command", &command_name), ("prefix", &prefix)],
+                ),
+            )
+        }
+        DispatchError::LackingPermissions(_) | DispatchError::LackingRole => (
+            ReplyKind::Error,
+            tr(
+                language,
+                "reply.lacking_permissions",
+                &[("command", &command_name)],
+            ),
+        ),
+        DispatchError::OnlyForGuilds => (
+            ReplyKind::Warning,
+            tr(
+                language,
+                "reply.only_for_guilds",
+                &[("command", &command_name)],
+            ),
+        ),
+        DispatchError::OnlyForDM => (
+            ReplyKind::Warning,
+            tr(language, "reply.only_for_dm", &[("command", &command_name)]),
+        ),
+        DispatchError::OnlyForOwners => (
+            ReplyKind::Error,
+            tr(
+                language,
+                "reply.only_for_owners",
+                &[("command", &command_name)],
+            ),
+        ),
+        _ => return,
+    };
+
+    if let Err(error) = send(ctx, message, kind, &text).await {
+        logger::log(
+            log::Level::Warn,
+            &format!(
+                "An error occured while trying to reply about dispatch error: {}",
+                error
+            ),
+        );
+    }
+}
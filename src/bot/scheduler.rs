@@ -0,0 +1,340 @@
+//! `scheduler` module implements persistent job scheduler that executes delayed tasks
+//! (e.g. lifting temporary punishments) even if bot was restarted in between.
+//!
+//! Jobs that failed are kept and retried with exponential backoff,
+//! until they succeed or `MAX_ATTEMPTS` is reached. Jobs that can not succeed
+//! (target no longer exists or bot is missing permissions) are dropped at once.
+//!
+
+use crate::{
+    bot::moderation::open_case,
+    db::{
+        connections::SERVERS_DB,
+        jobs,
+        models::{CaseAction, Job, LogCategory},
+    },
+    logger, metrics,
+};
+use serenity::{
+    http::{Http, HttpError},
+    model::{
+        id::{GuildId, RoleId, UserId},
+        Timestamp,
+    },
+};
+use sqlx::AnyPool;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Interval between two consecutive checks of due jobs.
+///
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Delay before first retry of failed job, which is doubled with every next attempt.
+///
+const RETRY_DELAY: i64 = 60;
+
+/// Number of attempts after which failed job is dropped.
+///
+const MAX_ATTEMPTS: i64 = 8;
+
+/// Whether polling loop was already started.
+///
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// `Task` enum represents action that should be executed when job is due.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Task {
+    /// Lifts temporary ban.
+    ///
+    Unban { guild_id: GuildId, user_id: UserId },
+    /// Removes temporary role.
+    ///
+    RemoveRole {
+        guild_id: GuildId,
+        user_id: UserId,
+        role_id: RoleId,
+    },
+}
+impl Task {
+    /// Returns kind of task as it is stored in database.
+    ///
+    fn kind(&self) -> &'static str {
+        match self {
+            Task::Unban { .. } => "unban",
+            Task::RemoveRole { .. } => "remove_role",
+        }
+    }
+
+    /// Returns payload of task as it is stored in database.
+    ///
+    fn payload(&self) -> String {
+        match self {
+            Task::Unban { guild_id, user_id } => format!("{} {}", guild_id, user_id),
+            Task::RemoveRole {
+                guild_id,
+                user_id,
+                role_id,
+            } => format!("{} {} {}", guild_id, user_id, role_id),
+        }
+    }
+
+    /// Returns guild that task is executed on.
+    ///
+    fn guild_id(&self) -> GuildId {
+        match *self {
+            Task::Unban { guild_id, .. } | Task::RemoveRole { guild_id, .. } => guild_id,
+        }
+    }
+
+    /// Restores task from database row.
+    ///
+    fn from_job(job: &Job) -> Option<Task> {
+        let ids: Vec<u64> = job
+            .payload
+            .split_whitespace()
+            .map(|id| id.parse::<u64>().ok())
+            .collect::<Option<Vec<u64>>>()?;
+        match (job.kind.as_str(), ids.as_slice()) {
+            ("unban", [guild_id, user_id]) => Some(Task::Unban {
+                guild_id: GuildId(*guild_id),
+                user_id: UserId(*user_id),
+            }),
+            ("remove_role", [guild_id, user_id, role_id]) => Some(Task::RemoveRole {
+                guild_id: GuildId(*guild_id),
+                user_id: UserId(*user_id),
+                role_id: RoleId(*role_id),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Executes task.
+    ///
+    async fn execute(&self, http: &Arc<Http>, bot_id: UserId) -> serenity::Result<()> {
+        match *self {
+            Task::Unban { guild_id, user_id } => {
+                guild_id.unban(http, user_id).await?;
+                if let Err(error) = open_case(
+                    http,
+                    guild_id,
+                    CaseAction::Unban,
+                    user_id.into(),
+                    bot_id.into(),
+                    Some("Temporary ban expired"),
+                    None,
+                )
+                .await
+                {
+                    // Task itself is done, so it should not be retried because of missing case.
+                    logger::log(
+                        log::Level::Warn,
+                        &format!("Could not open case for {:?} job: {}", self, error),
+                    );
+                }
+            }
+            Task::RemoveRole {
+                guild_id,
+                user_id,
+                role_id,
+            } => {
+                http.remove_member_role(
+                    guild_id.0,
+                    user_id.0,
+                    role_id.0,
+                    Some("Temporary role expired"),
+                )
+                .await?;
+                if let Err(error) = open_case(
+                    http,
+                    guild_id,
+                    CaseAction::RemoveRole,
+                    user_id.into(),
+                    bot_id.into(),
+                    Some(&format!("Temporary role <@&{}> expired", role_id)),
+                    None,
+                )
+                .await
+                {
+                    logger::log(
+                        log::Level::Warn,
+                        &format!("Could not open case for {:?} job: {}", self, error),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Schedules task to be executed at given unix timestamp.
+///
+pub async fn schedule(task: Task, due_at: i64) {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+    let _timer: metrics::Timer = metrics::Timer::query("jobs.schedule");
+
+    jobs::schedule(connection, task.kind(), &task.payload(), due_at)
+        .await
+        .expect("Query should be correct");
+}
+
+/// Cancels every scheduled execution of given task.
+///
+pub async fn cancel(task: Task) {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+    let _timer: metrics::Timer = metrics::Timer::query("jobs.cancel");
+
+    jobs::cancel(connection, task.kind(), &task.payload())
+        .await
+        .expect("Query should be correct");
+}
+
+/// Removes job from database.
+///
+async fn complete(connection: &AnyPool, job: &Job) {
+    let _timer: metrics::Timer = metrics::Timer::query("jobs.complete");
+    jobs::complete(connection, job)
+        .await
+        .expect("Query should be correct");
+}
+
+/// Postpones failed job, so that it is retried later.
+///
+async fn retry(connection: &AnyPool, job: &Job, attempts: i64, due_at: i64) {
+    let _timer: metrics::Timer = metrics::Timer::query("jobs.retry");
+    jobs::retry(connection, job, attempts, due_at)
+        .await
+        .expect("Query should be correct");
+}
+
+/// Returns HTTP status of error that discord responded with.
+///
+fn http_status(error: &serenity::Error) -> Option<u16> {
+    match error {
+        serenity::Error::Http(error) => match error.as_ref() {
+            HttpError::UnsuccessfulRequest(response) => Some(response.status_code.as_u16()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Executes all jobs that are due, removing ones that succeeded and postponing ones that failed.
+///
+pub async fn run_due(http: &Arc<Http>, bot_id: UserId) {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    let timer: metrics::Timer = metrics::Timer::query("jobs.due");
+    let due: Vec<Job> = jobs::due(connection, Timestamp::now().unix_timestamp())
+        .await
+        .expect("Query should be correct");
+    drop(timer);
+    for job in due {
+        let task: Task = match Task::from_job(&job) {
+            Some(task) => task,
+            None => {
+                logger::log(
+                    log::Level::Warn,
+                    &format!("Skipped malformed job #{} ('{}')", job.id, job.kind),
+                );
+                complete(connection, &job).await;
+                continue;
+            }
+        };
+        let error: serenity::Error = match task.execute(http, bot_id).await {
+            Ok(()) => {
+                complete(connection, &job).await;
+                continue;
+            }
+            Err(error) => error,
+        };
+        match http_status(&error) {
+            // Ban was already lifted, or member or role is gone, so there is nothing left to do.
+            Some(404) => {
+                complete(connection, &job).await;
+                logger::log(
+                    log::Level::Info,
+                    &format!(
+                        "Dropped {:?} job, since its target is gone: {}",
+                        task, error
+                    ),
+                );
+                continue;
+            }
+            Some(403) => {
+                complete(connection, &job).await;
+                logger::log_discord(
+                    http,
+                    task.guild_id(),
+                    LogCategory::Errors,
+                    &format!(
+                        "Dropped {:?} job, since bot is missing permissions: {}",
+                        task, error
+                    ),
+                );
+                continue;
+            }
+            _ => {}
+        }
+
+        let attempts: i64 = job.attempts + 1;
+        if attempts >= MAX_ATTEMPTS {
+            complete(connection, &job).await;
+            logger::log_discord(
+                http,
+                task.guild_id(),
+                LogCategory::Errors,
+                &format!(
+                    "Gave up executing {:?} job after {} attempts: {}",
+                    task, attempts, error
+                ),
+            );
+        } else {
+            let delay: i64 = RETRY_DELAY << (attempts - 1);
+            retry(
+                connection,
+                &job,
+                attempts,
+                Timestamp::now().unix_timestamp() + delay,
+            )
+            .await;
+            logger::log_discord(
+                http,
+                task.guild_id(),
+                LogCategory::Errors,
+                &format!(
+                    "An error occured while executing {:?} job (it will be retried in {} seconds): {}",
+                    task, delay, error
+                ),
+            );
+        }
+    }
+}
+
+/// Starts polling loop that executes due jobs. Subsequent calls (e.g. on reconnection) do nothing.
+///
+pub fn start(http: Arc<Http>, bot_id: UserId) {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut interval: tokio::time::Interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            let _ = interval.tick().await;
+            run_due(&http, bot_id).await;
+        }
+    });
+    logger::log(log::Level::Info, "Job scheduler was started");
+}
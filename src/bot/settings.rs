@@ -2,22 +2,38 @@
 //! are requiring administrator permissions to be called.
 //!
 
+// submodules
+pub mod transfer;
+
 use crate::{
-    db::{connections::SERVERS_DB, models::Id},
-    logger,
+    bot::{
+        audit::AuditEvent,
+        locale::{self, tr},
+        prefix, reply,
+    },
+    db::{
+        aliases, cache,
+        connections::SERVERS_DB,
+        models::{DbId, Id, Language, LogCategory, LogRoute, Setting},
+    },
+    logger, metrics,
 };
 use serenity::{
     client::Context,
     framework::standard::{
         macros::{command, group},
-        {Args, CommandResult},
+        {Args, CommandError, CommandResult},
     },
     model::{
-        channel::Message,
+        channel::{Channel, Message},
+        guild::Guild,
         id::{ChannelId, GuildId, RoleId},
+        Permissions,
     },
 };
-use sqlx::SqlitePool;
+use sqlx::{Any, AnyPool, Transaction};
+use std::{collections::HashMap, str::FromStr};
+use transfer::{EXPORT_COMMAND, IMPORT_COMMAND};
 
 /// `Settings` struct is a group for `serenity` framework that contains settings commands.
 ///
@@ -29,56 +45,164 @@ use sqlx::SqlitePool;
     set_moderation_channel,
     set_music_order_channel,
     set_music_log_channel,
-    set_member_role
+    set_member_role,
+    set_prefix,
+    set_language,
+    set_alias,
+    remove_alias,
+    toggle_log,
+    settings
 )]
 pub struct Settings;
 
+/// Settings that can be reset with `settings reset` command and columns they are stored in.
+///
+pub const RESETTABLE_SETTINGS: [(&str, &str); 7] = [
+    ("log_channel", "log_channel_id"),
+    ("moderation_channel", "moderation_channel_id"),
+    ("music_order_channel", "music_order_channel_id"),
+    ("music_log_channel", "music_log_channel_id"),
+    ("member_role", "member_role_id"),
+    ("prefix", "prefix"),
+    ("language", "language"),
+];
+
+/// Parses channel of current guild from arguments, or returns error that tells user
+/// why it is incorrect.
+///
+async fn parse_channel(
+    ctx: &Context,
+    message: &Message,
+    args: &mut Args,
+) -> Result<Id, CommandError> {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let argument: String = args.single::<String>().unwrap_or_default();
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+
+    let error: String = match Id::from_str(&argument) {
+        Ok(channel_id) => match ChannelId::from(channel_id).to_channel(&ctx.http).await {
+            Ok(channel)
+                if channel
+                    .guild()
+                    .is_some_and(|channel| channel.guild_id == guild_id) =>
+            {
+                return Ok(channel_id)
+            }
+            _ => tr(
+                language,
+                "settings.unknown_channel",
+                &[("channel", &argument)],
+            ),
+        },
+        Err(_) => tr(
+            language,
+            "settings.not_a_channel",
+            &[("argument", &argument)],
+        ),
+    };
+    Err(reply::user_error(error))
+}
+
+/// Parses role of current guild from arguments, or returns error that tells user
+/// why it is incorrect.
+///
+async fn parse_role(ctx: &Context, message: &Message, args: &mut Args) -> Result<Id, CommandError> {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("This should be called only on guilds");
+    let argument: String = args.single::<String>().unwrap_or_default();
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+
+    let error: String = match Id::from_str(&argument) {
+        Ok(role_id) => match guild_id.roles(&ctx.http).await {
+            Ok(roles) if roles.contains_key(&RoleId::from(role_id)) => return Ok(role_id),
+            _ => tr(language, "settings.unknown_role", &[("role", &argument)]),
+        },
+        Err(_) => tr(language, "settings.not_a_role", &[("argument", &argument)]),
+    };
+    Err(reply::user_error(error))
+}
+
 macro_rules! setup_channel {
-    ($fullname:ident, $name:ident) => {
+    ($fullname:ident, $name:ident $(, #[$meta:meta])*) => {
         #[command]
+        #[usage("<channel>")]
+        #[example("#logs")]
         #[num_args(1)]
+        $(#[$meta])*
         pub async fn $fullname(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
-            let connection: &SqlitePool = SERVERS_DB.get().expect("Connection should already be established at this moment");
+            let connection: &AnyPool = SERVERS_DB.get().expect("Connection should already be established at this moment");
 
             let guild_id: Id = message.guild_id.expect(concat!(stringify!($fullname), " command can be called only on guild")).into();
-            let channel_id: Id = args.single::<Id>()?.into();
-            if ChannelId::from(channel_id).to_channel(&ctx.http).await.is_err() {
-                return Ok(());
-            }
-            if sqlx::query("SELECT discord_id FROM channels WHERE discord_id = ?")
-                .bind(channel_id.to_string())
-                .fetch_optional(connection)
+            let channel_id: Id = parse_channel(ctx, message, &mut args).await?;
+            if metrics::timed(
+                "channels.find",
+                sqlx::query("SELECT discord_id FROM channels WHERE discord_id = $1")
+                    .bind(channel_id.db_id())
+                    .fetch_optional(connection),
+            )
                 .await
                 .expect("Query should be correct").is_some() {
-                sqlx::query("DELETE FROM channels WHERE discord_id = ?")
-                    .bind(channel_id.to_string())
-                    .execute(connection)
+                metrics::timed(
+                    "channels.delete",
+                    sqlx::query("DELETE FROM channels WHERE discord_id = $1")
+                        .bind(channel_id.db_id())
+                        .execute(connection),
+                )
                     .await
                     .expect("Query should be correct");
             }
 
-            sqlx::query(concat!("
-                INSERT INTO channels VALUES (?, ?);
-                UPDATE settings SET ", stringify!($name), "_id = (SELECT last_insert_rowid()) WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = ?)"
-            )).bind(channel_id.to_string()).bind(guild_id.to_string())
-            .bind(guild_id.to_string()).execute(connection).await.expect("Query should be correct");
+            let mut transaction: Transaction<'_, Any> = connection.begin().await.expect("Transaction should be started");
+            metrics::timed(
+                "channels.insert",
+                sqlx::query("INSERT INTO channels VALUES ($1, $2)")
+                    .bind(channel_id.db_id()).bind(guild_id.db_id())
+                    .execute(&mut *transaction),
+            ).await.expect("Query should be correct");
+            metrics::timed(
+                "settings.set_channel",
+                sqlx::query(concat!("UPDATE settings SET ", stringify!($name), "_id = $1 WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = $2)"))
+                    .bind(channel_id.db_id()).bind(guild_id.db_id())
+                    .execute(&mut *transaction),
+            ).await.expect("Query should be correct");
+            transaction.commit().await.expect("Transaction should be committed");
+            cache::invalidate(GuildId::from(guild_id));
+            let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+            reply::success(ctx, message, &tr(language, "settings.channel_set", &[("setting", &stringify!($name)), ("channel", &format!("<#{}>", channel_id.0))])).await?;
 
             logger::log(log::Level::Info, concat!(stringify!($fullname), " was called"));
             Ok(())
         }
     };
 }
-setup_channel!(set_log_channel, log_channel);
+setup_channel!(
+    set_log_channel,
+    log_channel,
+    #[sub_commands(
+        registration,
+        moderation,
+        music,
+        messages,
+        voice,
+        server_changes,
+        errors
+    )]
+);
 setup_channel!(set_moderation_channel, moderation_channel);
 setup_channel!(set_music_order_channel, music_order_channel);
 setup_channel!(set_music_log_channel, music_log_channel);
 
-macro_rules! setup_roles {
-    ($fullname:ident, $name:ident) => {
+macro_rules! setup_log_route {
+    ($fullname:ident, $category:expr) => {
         #[command]
+        #[usage("<channel>")]
+        #[example("#logs")]
         #[num_args(1)]
         pub async fn $fullname(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
-            let connection: &SqlitePool = SERVERS_DB
+            let connection: &AnyPool = SERVERS_DB
                 .get()
                 .expect("Connection should already be established at this moment");
 
@@ -89,48 +213,142 @@ macro_rules! setup_roles {
                     " command can be called only on guild"
                 ))
                 .into();
-            let role_id: Id = args.single::<Id>()?.into();
-            if !GuildId::from(guild_id)
-                .roles(&ctx.http)
-                .await
+            let channel_id: Id = parse_channel(ctx, message, &mut args).await?;
+
+            metrics::timed(
+                "log_routes.upsert",
+                sqlx::query(
+                    "
+                    INSERT INTO log_routes VALUES ($1, $2, $3)
+                    ON CONFLICT (guild_id, category) DO UPDATE SET channel_id = excluded.channel_id
+                ",
+                )
+                .bind(guild_id.db_id())
+                .bind($category.to_string())
+                .bind(channel_id.db_id())
+                .execute(connection),
+            )
+            .await
+            .expect("Query should be correct");
+            let language: Language =
+                locale::user_language(message.guild_id, message.author.id).await;
+            reply::success(
+                ctx,
+                message,
+                &tr(
+                    language,
+                    "settings.log_route_set",
+                    &[
+                        ("category", &$category.as_str()),
+                        ("channel", &format!("<#{}>", channel_id.0)),
+                    ],
+                ),
+            )
+            .await?;
+
+            logger::log(
+                log::Level::Info,
+                concat!("set_log_channel ", stringify!($fullname), " was called"),
+            );
+            Ok(())
+        }
+    };
+}
+setup_log_route!(registration, LogCategory::Registration);
+setup_log_route!(moderation, LogCategory::Moderation);
+setup_log_route!(music, LogCategory::Music);
+setup_log_route!(messages, LogCategory::Messages);
+setup_log_route!(voice, LogCategory::Voice);
+setup_log_route!(server_changes, LogCategory::ServerChanges);
+setup_log_route!(errors, LogCategory::Errors);
+
+macro_rules! setup_roles {
+    ($fullname:ident, $name:ident) => {
+        #[command]
+        #[usage("<role>")]
+        #[example("@Member")]
+        #[num_args(1)]
+        pub async fn $fullname(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+            let connection: &AnyPool = SERVERS_DB
+                .get()
+                .expect("Connection should already be established at this moment");
+
+            let guild_id: Id = message
+                .guild_id
                 .expect(concat!(
                     stringify!($fullname),
                     " command can be called only on guild"
                 ))
-                .contains_key(&RoleId::from(role_id))
+                .into();
+            let role_id: Id = parse_role(ctx, message, &mut args).await?;
+            if metrics::timed(
+                "roles.find",
+                sqlx::query("SELECT discord_id FROM roles WHERE discord_id = $1 AND guild_id = $2")
+                    .bind(role_id.db_id())
+                    .bind(guild_id.db_id())
+                    .fetch_optional(connection),
+            )
+            .await
+            .expect("Query should be correct")
+            .is_some()
             {
-                return Ok(());
-            }
-            if sqlx::query("SELECT discord_id FROM roles WHERE discord_id = ? AND guild_id = ?")
-                .bind(role_id.to_string())
-                .bind(guild_id.to_string())
-                .fetch_optional(connection)
+                metrics::timed(
+                    "roles.delete",
+                    sqlx::query("DELETE FROM roles WHERE discord_id = $1 AND guild_id = $2")
+                        .bind(role_id.db_id())
+                        .bind(guild_id.db_id())
+                        .execute(connection),
+                )
                 .await
-                .expect("Query should be correct")
-                .is_some()
-            {
-                sqlx::query("DELETE FROM roles WHERE discord_id = ? AND guild_id = ?")
-                    .bind(role_id.to_string())
-                    .bind(guild_id.to_string())
-                    .execute(connection)
-                    .await
-                    .expect("Query should be correct");
+                .expect("Query should be correct");
             }
 
-            sqlx::query(concat!(
-                "
-                INSERT INTO roles VALUES (?, ?);
-                UPDATE settings SET ",
-                stringify!($name),
-                "_id = ? WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = ?)"
-            ))
-            .bind(role_id.to_string())
-            .bind(guild_id.to_string())
-            .bind(role_id.to_string())
-            .bind(guild_id.to_string())
-            .execute(connection)
+            let mut transaction: Transaction<'_, Any> = connection
+                .begin()
+                .await
+                .expect("Transaction should be started");
+            metrics::timed(
+                "roles.insert",
+                sqlx::query("INSERT INTO roles VALUES ($1, $2)")
+                    .bind(role_id.db_id())
+                    .bind(guild_id.db_id())
+                    .execute(&mut *transaction),
+            )
+            .await
+            .expect("Query should be correct");
+            metrics::timed(
+                "settings.set_role",
+                sqlx::query(concat!(
+                    "UPDATE settings SET ",
+                    stringify!($name),
+                    "_id = $1 WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = $2)"
+                ))
+                .bind(role_id.db_id())
+                .bind(guild_id.db_id())
+                .execute(&mut *transaction),
+            )
             .await
             .expect("Query should be correct");
+            transaction
+                .commit()
+                .await
+                .expect("Transaction should be committed");
+            cache::invalidate(GuildId::from(guild_id));
+            let language: Language =
+                locale::user_language(message.guild_id, message.author.id).await;
+            reply::success(
+                ctx,
+                message,
+                &tr(
+                    language,
+                    "settings.role_set",
+                    &[
+                        ("setting", &stringify!($name)),
+                        ("role", &format!("<@&{}>", role_id.0)),
+                    ],
+                ),
+            )
+            .await?;
 
             logger::log(
                 log::Level::Info,
@@ -141,3 +359,697 @@ macro_rules! setup_roles {
     };
 }
 setup_roles!(set_member_role, member_role);
+
+#[command]
+#[usage("<event>")]
+#[example("message_edit")]
+#[num_args(1)]
+pub async fn toggle_log(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should already be established at this moment");
+
+    let guild_id: Id = message
+        .guild_id
+        .expect("toggle_log command can be called only on guild")
+        .into();
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let event: AuditEvent = match AuditEvent::from_str(&args.single::<String>()?) {
+        Ok(event) => event,
+        Err(error) => {
+            let events: Vec<&str> = AuditEvent::ALL
+                .iter()
+                .map(|event| event.as_str())
+                .collect::<Vec<&str>>();
+            reply::warning(
+                ctx,
+                message,
+                &tr(
+                    language,
+                    "settings.unknown_event",
+                    &[("error", &error), ("events", &events.join(", "))],
+                ),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    let Setting {
+        disabled_log_events,
+        ..
+    } = metrics::timed(
+        "settings.toggle_log",
+        sqlx::query_as::<_, Setting>(
+            "
+            UPDATE settings SET disabled_log_events = (disabled_log_events | $1) - (disabled_log_events & $2) WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = $3)
+            RETURNING disabled_log_events
+        ",
+        )
+        .bind(event.bit())
+        .bind(event.bit())
+        .bind(guild_id.db_id())
+        .fetch_one(connection),
+    )
+    .await
+    .expect("Query should be correct");
+    cache::invalidate(GuildId::from(guild_id));
+    reply::success(
+        ctx,
+        message,
+        &tr(
+            language,
+            if disabled_log_events & event.bit() == 0 {
+                "settings.log_event_enabled"
+            } else {
+                "settings.log_event_disabled"
+            },
+            &[("event", &event.as_str())],
+        ),
+    )
+    .await?;
+
+    logger::log(log::Level::Info, "toggle_log was called");
+    Ok(())
+}
+
+#[command]
+#[usage("<prefix>")]
+#[example("!")]
+#[num_args(1)]
+pub async fn set_prefix(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should already be established at this moment");
+
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("set_prefix command can be called only on guild");
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let new_prefix: String = args.single::<String>()?;
+    if new_prefix.chars().count() > prefix::MAX_PREFIX_LENGTH {
+        reply::warning(
+            ctx,
+            message,
+            &tr(
+                language,
+                "settings.prefix_too_long",
+                &[("count", &prefix::MAX_PREFIX_LENGTH)],
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    metrics::timed(
+        "settings.set_prefix",
+        sqlx::query(
+            "UPDATE settings SET prefix = $1 WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = $2)",
+        )
+        .bind(&new_prefix)
+        .bind(guild_id.db_id())
+        .execute(connection),
+    )
+    .await
+    .expect("Query should be correct");
+    cache::invalidate(guild_id);
+    reply::success(
+        ctx,
+        message,
+        &tr(language, "settings.prefix_set", &[("prefix", &new_prefix)]),
+    )
+    .await?;
+
+    logger::log(log::Level::Info, "set_prefix was called");
+    Ok(())
+}
+
+#[command]
+#[usage("<language>")]
+#[example("ru")]
+#[num_args(1)]
+pub async fn set_language(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should already be established at this moment");
+
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("set_language command can be called only on guild");
+    let language: Language = locale::parse_language(message, &mut args).await?;
+
+    metrics::timed(
+        "settings.set_language",
+        sqlx::query(
+            "UPDATE settings SET language = $1 WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = $2)",
+        )
+        .bind(language.to_string())
+        .bind(guild_id.db_id())
+        .execute(connection),
+    )
+    .await
+    .expect("Query should be correct");
+    cache::invalidate(guild_id);
+    let reply_language: Language = locale::user_language(Some(guild_id), message.author.id).await;
+    reply::success(
+        ctx,
+        message,
+        &tr(
+            reply_language,
+            "settings.language_set",
+            &[("language", &language.as_str())],
+        ),
+    )
+    .await?;
+
+    logger::log(log::Level::Info, "set_language was called");
+    Ok(())
+}
+
+#[command]
+#[usage("<alias> <command>")]
+#[example("бан ban")]
+#[min_args(2)]
+pub async fn set_alias(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should already be established at this moment");
+
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("set_alias command can be called only on guild");
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let alias: String = args.single::<String>()?;
+    let command: String = args.rest().trim().to_string();
+    let command_name: &str = command.split_whitespace().next().unwrap_or_default();
+
+    if !prefix::is_command(command_name) {
+        return Err(reply::user_error(tr(
+            language,
+            "settings.alias_unknown_command",
+            &[("command", &command_name)],
+        )));
+    }
+    let aliases: HashMap<String, String> = prefix::get_aliases(guild_id).await;
+    if alias == command_name
+        || aliases.contains_key(command_name)
+        || aliases
+            .values()
+            .any(|command| command.split_whitespace().next() == Some(alias.as_str()))
+    {
+        return Err(reply::user_error(tr(language, "settings.alias_chain", &[])));
+    }
+
+    metrics::timed(
+        "command_aliases.upsert",
+        aliases::set(connection, guild_id.into(), &alias, &command),
+    )
+    .await
+    .expect("Query should be correct");
+    prefix::invalidate(guild_id);
+    reply::success(
+        ctx,
+        message,
+        &tr(
+            language,
+            "settings.alias_set",
+            &[("alias", &alias), ("command", &command)],
+        ),
+    )
+    .await?;
+
+    logger::log(log::Level::Info, "set_alias was called");
+    Ok(())
+}
+
+#[command]
+#[usage("<alias>")]
+#[example("бан")]
+#[num_args(1)]
+pub async fn remove_alias(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should already be established at this moment");
+
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("remove_alias command can be called only on guild");
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let alias: String = args.single::<String>()?;
+
+    let is_removed: bool = metrics::timed(
+        "command_aliases.delete",
+        aliases::remove(connection, guild_id.into(), &alias),
+    )
+    .await
+    .expect("Query should be correct");
+    prefix::invalidate(guild_id);
+    if is_removed {
+        reply::success(
+            ctx,
+            message,
+            &tr(language, "settings.alias_removed", &[("alias", &alias)]),
+        )
+        .await?;
+    } else {
+        reply::warning(
+            ctx,
+            message,
+            &tr(language, "settings.alias_missing", &[("alias", &alias)]),
+        )
+        .await?;
+    }
+
+    logger::log(log::Level::Info, "remove_alias was called");
+    Ok(())
+}
+
+/// Fetches settings and log routes of guild.
+///
+async fn get_settings(guild_id: GuildId) -> (Setting, Vec<LogRoute>) {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should already be established at this moment");
+    let _timer: metrics::Timer = metrics::Timer::query("settings.get");
+
+    let setting: Setting = sqlx::query_as::<_, Setting>(
+        "SELECT * FROM settings WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = $1)",
+    )
+    .bind(guild_id.db_id())
+    .fetch_one(connection)
+    .await
+    .expect("Query should be correct");
+    let log_routes: Vec<LogRoute> =
+        sqlx::query_as::<_, LogRoute>("SELECT * FROM log_routes WHERE guild_id = $1")
+            .bind(guild_id.db_id())
+            .fetch_all(connection)
+            .await
+            .expect("Query should be correct");
+    (setting, log_routes)
+}
+
+/// Renders settings of guild in human-readable form.
+///
+async fn render_settings(language: Language, guild_id: GuildId) -> String {
+    let (setting, log_routes) = get_settings(guild_id).await;
+    let name = |setting: &str| tr(language, &format!("settings.name.{}", setting), &[]);
+    let channel = |channel_id: Option<u64>| {
+        channel_id.map_or(tr(language, "settings.not_set", &[]), |channel_id| {
+            format!("<#{}>", channel_id)
+        })
+    };
+
+    let mut lines: Vec<String> = vec![
+        format!(
+            "{}: `{}`",
+            name("prefix"),
+            prefix::get_prefix(Some(guild_id)).await
+        ),
+        format!("{}: {}", name("language"), language_of(&setting).as_str()),
+        format!(
+            "{}: {}",
+            name("log_channel"),
+            channel(setting.log_channel_id.0)
+        ),
+        format!(
+            "{}: {}",
+            name("moderation_channel"),
+            channel(setting.moderation_channel_id.0)
+        ),
+        format!(
+            "{}: {}",
+            name("music_order_channel"),
+            channel(setting.music_order_channel_id.0)
+        ),
+        format!(
+            "{}: {}",
+            name("music_log_channel"),
+            channel(setting.music_log_channel_id.0)
+        ),
+        format!(
+            "{}: {}",
+            name("member_role"),
+            setting.member_role_id.0.map_or(
+                tr(language, "settings.not_set", &[]),
+                |role_id| format!("<@&{}>", role_id)
+            )
+        ),
+    ];
+    for category in LogCategory::ALL {
+        let route: Option<u64> = log_routes
+            .iter()
+            .find(|route| route.category == category)
+            .map(|route| route.channel_id.0);
+        lines.push(format!(
+            "{}: {}",
+            tr(
+                language,
+                "settings.name.log_route",
+                &[("category", &category.as_str())]
+            ),
+            route.map_or(
+                tr(language, "settings.default_log_channel", &[]),
+                |channel_id| format!("<#{}>", channel_id)
+            )
+        ));
+    }
+    let disabled_events: Vec<&str> = AuditEvent::ALL
+        .iter()
+        .filter(|event| setting.disabled_log_events & event.bit() != 0)
+        .map(|event| event.as_str())
+        .collect::<Vec<&str>>();
+    lines.push(format!(
+        "{}: {}",
+        name("disabled_log_events"),
+        if disabled_events.is_empty() {
+            tr(language, "settings.none", &[])
+        } else {
+            disabled_events.join(", ")
+        }
+    ));
+    let mut aliases: Vec<(String, String)> = prefix::get_aliases(guild_id)
+        .await
+        .into_iter()
+        .collect::<Vec<(String, String)>>();
+    aliases.sort();
+    lines.push(format!(
+        "{}: {}",
+        name("aliases"),
+        if aliases.is_empty() {
+            tr(language, "settings.none", &[])
+        } else {
+            aliases
+                .iter()
+                .map(|(alias, command)| format!("`{}` → `{}`", alias, command))
+                .collect::<Vec<String>>()
+                .join(", ")
+        }
+    ));
+    lines.join("\n")
+}
+
+/// Returns language that is set in guild settings.
+///
+fn language_of(setting: &Setting) -> Language {
+    setting
+        .language
+        .as_deref()
+        .and_then(|language| Language::from_str(language).ok())
+        .unwrap_or_default()
+}
+
+/// Checks that channel exists on guild and bot has given permissions in it.
+///
+async fn validate_channel(
+    ctx: &Context,
+    language: Language,
+    guild_id: GuildId,
+    name: &str,
+    channel_id: Option<u64>,
+    required: Permissions,
+) -> String {
+    let channel_id: u64 = match channel_id {
+        Some(channel_id) => channel_id,
+        None => return tr(language, "settings.validate.not_set", &[("name", &name)]),
+    };
+    let mention: String = format!("<#{}>", channel_id);
+    let channel = match ChannelId(channel_id).to_channel(&ctx.http).await {
+        Ok(Channel::Guild(channel)) if channel.guild_id == guild_id => channel,
+        _ => {
+            return tr(
+                language,
+                "settings.validate.unknown_channel",
+                &[("name", &name), ("channel", &channel_id)],
+            )
+        }
+    };
+    match channel.permissions_for_user(&ctx.cache, ctx.cache.current_user_id()) {
+        Ok(permissions) if permissions.contains(required) => tr(
+            language,
+            "settings.validate.ok",
+            &[("name", &name), ("value", &mention)],
+        ),
+        Ok(permissions) => tr(
+            language,
+            "settings.validate.missing_permissions",
+            &[
+                ("name", &name),
+                ("permissions", &format!("{:?}", required - permissions)),
+                ("channel", &mention),
+            ],
+        ),
+        Err(error) => tr(
+            language,
+            "settings.validate.permissions_error",
+            &[("name", &name), ("channel", &mention), ("error", &error)],
+        ),
+    }
+}
+
+/// Checks that member role exists on guild and bot is able to assign it.
+///
+async fn validate_role(
+    ctx: &Context,
+    language: Language,
+    guild_id: GuildId,
+    role_id: Option<u64>,
+) -> String {
+    let name: String = tr(language, "settings.name.member_role", &[]);
+    let role_id: RoleId = match role_id {
+        Some(role_id) => RoleId(role_id),
+        None => return tr(language, "settings.validate.not_set", &[("name", &name)]),
+    };
+    let mention: String = format!("<@&{}>", role_id);
+    let guild: Guild = match guild_id.to_guild_cached(&ctx.cache) {
+        Some(guild) => guild,
+        None => {
+            return tr(
+                language,
+                "settings.validate.guild_not_cached",
+                &[("name", &name)],
+            )
+        }
+    };
+    let role_position: i64 = match guild.roles.get(&role_id) {
+        Some(role) => role.position,
+        None => {
+            return tr(
+                language,
+                "settings.validate.unknown_role",
+                &[("name", &name), ("role", &role_id)],
+            )
+        }
+    };
+    let bot = match guild.member(ctx, ctx.cache.current_user_id()).await {
+        Ok(bot) => bot,
+        Err(error) => {
+            return tr(
+                language,
+                "settings.validate.bot_member_error",
+                &[("name", &name), ("error", &error)],
+            )
+        }
+    };
+
+    if !bot
+        .permissions(&ctx.cache)
+        .is_ok_and(|permissions| permissions.manage_roles())
+    {
+        return tr(
+            language,
+            "settings.validate.missing_manage_roles",
+            &[("name", &name)],
+        );
+    }
+    match bot.highest_role_info(&ctx.cache) {
+        Some((_, position)) if position > role_position => tr(
+            language,
+            "settings.validate.ok",
+            &[("name", &name), ("value", &mention)],
+        ),
+        _ => tr(
+            language,
+            "settings.validate.role_too_high",
+            &[("name", &name), ("role", &mention)],
+        ),
+    }
+}
+
+#[command]
+#[num_args(0)]
+#[sub_commands(show, reset, validate, export, import)]
+pub async fn settings(ctx: &Context, message: &Message, args: Args) -> CommandResult {
+    show(ctx, message, args).await
+}
+
+#[command]
+#[num_args(0)]
+pub async fn show(ctx: &Context, message: &Message, _: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("settings show command can be called only on guild");
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+
+    reply::info(ctx, message, &render_settings(language, guild_id).await).await?;
+
+    logger::log(log::Level::Info, "settings show was called");
+    Ok(())
+}
+
+#[command]
+#[usage("<setting>")]
+#[example("prefix")]
+#[num_args(1)]
+pub async fn reset(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should already be established at this moment");
+
+    let guild_id: Id = message
+        .guild_id
+        .expect("settings reset command can be called only on guild")
+        .into();
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let key: String = args.single::<String>()?;
+
+    if let Some((_, column)) = RESETTABLE_SETTINGS.iter().find(|(name, _)| *name == key) {
+        metrics::timed(
+            "settings.reset",
+            sqlx::query(&format!(
+                "UPDATE settings SET {} = NULL WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = $1)",
+                column
+            ))
+            .bind(guild_id.db_id())
+            .execute(connection),
+        )
+        .await
+        .expect("Query should be correct");
+    } else if key == "aliases" {
+        metrics::timed(
+            "command_aliases.reset",
+            aliases::clear(connection, guild_id.into()),
+        )
+        .await
+        .expect("Query should be correct");
+    } else if key == "log_events" {
+        metrics::timed(
+            "settings.reset_log_events",
+            sqlx::query("UPDATE settings SET disabled_log_events = 0 WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = $1)")
+                .bind(guild_id.db_id())
+                .execute(connection),
+        )
+            .await
+            .expect("Query should be correct");
+    } else if let Ok(category) = LogCategory::from_str(&key) {
+        metrics::timed(
+            "log_routes.reset",
+            sqlx::query("DELETE FROM log_routes WHERE guild_id = $1 AND category = $2")
+                .bind(guild_id.db_id())
+                .bind(category.to_string())
+                .execute(connection),
+        )
+        .await
+        .expect("Query should be correct");
+    } else {
+        let keys: Vec<&str> = RESETTABLE_SETTINGS
+            .iter()
+            .map(|(name, _)| *name)
+            .chain(["aliases", "log_events"])
+            .chain(LogCategory::ALL.iter().map(|category| category.as_str()))
+            .collect::<Vec<&str>>();
+        reply::warning(
+            ctx,
+            message,
+            &tr(
+                language,
+                "settings.unknown_setting",
+                &[("setting", &key), ("settings", &keys.join(", "))],
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+    cache::invalidate(GuildId::from(guild_id));
+    prefix::invalidate(GuildId::from(guild_id));
+    reply::success(
+        ctx,
+        message,
+        &tr(language, "settings.reset", &[("setting", &key)]),
+    )
+    .await?;
+
+    logger::log(log::Level::Info, "settings reset was called");
+    Ok(())
+}
+
+#[command]
+#[num_args(0)]
+pub async fn validate(ctx: &Context, message: &Message, _: Args) -> CommandResult {
+    let guild_id: GuildId = message
+        .guild_id
+        .expect("settings validate command can be called only on guild");
+    let language: Language = locale::user_language(message.guild_id, message.author.id).await;
+    let (setting, log_routes) = get_settings(guild_id).await;
+    let send: Permissions = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES;
+
+    let mut lines: Vec<String> = vec![
+        validate_channel(
+            ctx,
+            language,
+            guild_id,
+            &tr(language, "settings.name.log_channel", &[]),
+            setting.log_channel_id.0,
+            send | Permissions::EMBED_LINKS,
+        )
+        .await,
+        validate_channel(
+            ctx,
+            language,
+            guild_id,
+            &tr(language, "settings.name.moderation_channel", &[]),
+            setting.moderation_channel_id.0,
+            send,
+        )
+        .await,
+        validate_channel(
+            ctx,
+            language,
+            guild_id,
+            &tr(language, "settings.name.music_order_channel", &[]),
+            setting.music_order_channel_id.0,
+            send,
+        )
+        .await,
+        validate_channel(
+            ctx,
+            language,
+            guild_id,
+            &tr(language, "settings.name.music_log_channel", &[]),
+            setting.music_log_channel_id.0,
+            send,
+        )
+        .await,
+        validate_role(ctx, language, guild_id, setting.member_role_id.0).await,
+    ];
+    for route in log_routes {
+        lines.push(
+            validate_channel(
+                ctx,
+                language,
+                guild_id,
+                &tr(
+                    language,
+                    "settings.name.log_route",
+                    &[("category", &route.category.as_str())],
+                ),
+                Some(route.channel_id.0),
+                send | Permissions::EMBED_LINKS,
+            )
+            .await,
+        );
+    }
+    reply::info(ctx, message, &lines.join("\n")).await?;
+
+    logger::log(log::Level::Info, "settings validate was called");
+    Ok(())
+}
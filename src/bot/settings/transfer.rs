@@ -0,0 +1,764 @@
+//! `transfer` module implements export of guild settings into JSON document and
+//! import of such documents into other guilds.
+//!
+
+use crate::{
+    bot::{
+        audit::AuditEvent,
+        automod,
+        locale::{self, tr},
+        prefix,
+        reply::{self, ReplyKind},
+    },
+    db::{
+        aliases, cache,
+        connections::SERVERS_DB,
+        models::{
+            AutomodAction, AutomodRule, AutomodRuleKind, CommandAlias, DbId, Language, LogCategory,
+            LogRoute, MusicBot, RaidSetting, Setting,
+        },
+    },
+    logger, metrics,
+};
+use serde::{Deserialize, Serialize};
+use serenity::{
+    client::Context,
+    framework::standard::{macros::command, Args, CommandResult},
+    model::{
+        channel::{AttachmentType, Channel, Message},
+        guild::Guild,
+        id::{ChannelId, GuildId, RoleId},
+    },
+};
+use sqlx::{Any, AnyPool, Transaction};
+use std::{borrow::Cow, collections::BTreeMap, str::FromStr};
+
+/// Version of settings document format.
+///
+const DOCUMENT_VERSION: u32 = 1;
+
+/// `Reference` struct represents channel or role of guild.
+///
+/// Name is stored alongside id so that document could be imported into another guild.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reference {
+    pub id: u64,
+    pub name: String,
+}
+
+/// `AutomodRuleEntry` struct represents automod rule in settings document.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutomodRuleEntry {
+    pub kind: String,
+    pub threshold: Option<i64>,
+    pub period: Option<i64>,
+    pub pattern: Option<String>,
+    pub action: String,
+}
+
+/// `RaidEntry` struct represents raid protection settings in settings document.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RaidEntry {
+    pub join_threshold: Option<i64>,
+    pub join_period: Option<i64>,
+    pub min_account_age: Option<i64>,
+    pub quarantine_role: Option<Reference>,
+}
+
+/// `SettingsDocument` struct represents all settings of guild in portable form.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingsDocument {
+    pub version: u32,
+    pub prefix: Option<String>,
+    pub language: Option<String>,
+    pub aliases: BTreeMap<String, String>,
+    pub log_channel: Option<Reference>,
+    pub moderation_channel: Option<Reference>,
+    pub music_order_channel: Option<Reference>,
+    pub music_log_channel: Option<Reference>,
+    pub member_role: Option<Reference>,
+    pub disabled_log_events: Vec<String>,
+    pub log_routes: BTreeMap<String, Reference>,
+    pub automod_rules: Vec<AutomodRuleEntry>,
+    pub raid: Option<RaidEntry>,
+    pub music_bots: Vec<String>,
+}
+
+/// Returns name of guild channel or empty string if it is unknown.
+///
+fn channel_name(guild: &Guild, channel_id: u64) -> String {
+    match guild.channels.get(&ChannelId(channel_id)) {
+        Some(Channel::Guild(channel)) => channel.name.clone(),
+        _ => String::new(),
+    }
+}
+
+/// Returns name of guild role or empty string if it is unknown.
+///
+fn role_name(guild: &Guild, role_id: u64) -> String {
+    guild
+        .roles
+        .get(&RoleId(role_id))
+        .map(|role| role.name.clone())
+        .unwrap_or_default()
+}
+
+/// Builds settings document of guild from database.
+///
+pub async fn export_settings(guild: &Guild) -> SettingsDocument {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should already be established at this moment");
+    let _timer: metrics::Timer = metrics::Timer::query("settings.export");
+
+    let setting: Setting = sqlx::query_as::<_, Setting>(
+        "SELECT * FROM settings WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = $1)",
+    )
+    .bind(guild.id.db_id())
+    .fetch_one(connection)
+    .await
+    .expect("Query should be correct");
+    let log_routes: Vec<LogRoute> =
+        sqlx::query_as::<_, LogRoute>("SELECT * FROM log_routes WHERE guild_id = $1")
+            .bind(guild.id.db_id())
+            .fetch_all(connection)
+            .await
+            .expect("Query should be correct");
+    let automod_rules: Vec<AutomodRule> =
+        sqlx::query_as::<_, AutomodRule>("SELECT * FROM automod_rules WHERE guild_id = $1")
+            .bind(guild.id.db_id())
+            .fetch_all(connection)
+            .await
+            .expect("Query should be correct");
+    let raid_setting: Option<RaidSetting> =
+        sqlx::query_as::<_, RaidSetting>("SELECT * FROM raid_settings WHERE guild_id = $1")
+            .bind(guild.id.db_id())
+            .fetch_optional(connection)
+            .await
+            .expect("Query should be correct");
+    let aliases: Vec<CommandAlias> = aliases::list(connection, guild.id.into())
+        .await
+        .expect("Query should be correct");
+    let music_bots: Vec<MusicBot> =
+        sqlx::query_as::<_, MusicBot>("SELECT * FROM music_bots WHERE guild_id = $1")
+            .bind(guild.id.db_id())
+            .fetch_all(connection)
+            .await
+            .expect("Query should be correct");
+
+    let channel = |channel_id: Option<u64>| {
+        channel_id.map(|id| Reference {
+            id,
+            name: channel_name(guild, id),
+        })
+    };
+    let role = |role_id: Option<u64>| {
+        role_id.map(|id| Reference {
+            id,
+            name: role_name(guild, id),
+        })
+    };
+    SettingsDocument {
+        version: DOCUMENT_VERSION,
+        prefix: setting.prefix,
+        language: setting.language,
+        aliases: aliases
+            .into_iter()
+            .map(|alias| (alias.alias, alias.command))
+            .collect::<BTreeMap<String, String>>(),
+        log_channel: channel(setting.log_channel_id.0),
+        moderation_channel: channel(setting.moderation_channel_id.0),
+        music_order_channel: channel(setting.music_order_channel_id.0),
+        music_log_channel: channel(setting.music_log_channel_id.0),
+        member_role: role(setting.member_role_id.0),
+        disabled_log_events: AuditEvent::ALL
+            .iter()
+            .filter(|event| setting.disabled_log_events & event.bit() != 0)
+            .map(|event| event.as_str().to_string())
+            .collect::<Vec<String>>(),
+        log_routes: log_routes
+            .into_iter()
+            .filter_map(|route| {
+                Some((
+                    route.category.to_string(),
+                    channel(Some(route.channel_id.0))?,
+                ))
+            })
+            .collect::<BTreeMap<String, Reference>>(),
+        automod_rules: automod_rules
+            .into_iter()
+            .map(|rule| AutomodRuleEntry {
+                kind: rule.kind.to_string(),
+                threshold: rule.threshold,
+                period: rule.period,
+                pattern: rule.pattern,
+                action: rule.action.to_string(),
+            })
+            .collect::<Vec<AutomodRuleEntry>>(),
+        raid: raid_setting.map(|raid_setting| RaidEntry {
+            join_threshold: raid_setting.join_threshold,
+            join_period: raid_setting.join_period,
+            min_account_age: raid_setting.min_account_age,
+            quarantine_role: role(raid_setting.quarantine_role_id.0),
+        }),
+        music_bots: music_bots
+            .into_iter()
+            .map(|music_bot| music_bot.prefix.trim().to_string())
+            .collect::<Vec<String>>(),
+    }
+}
+
+/// Finds channel of guild by id, or by name if there is no channel with such id.
+///
+fn resolve_channel(guild: &Guild, reference: &Reference) -> Option<Reference> {
+    guild
+        .channels
+        .values()
+        .filter_map(|channel| match channel {
+            Channel::Guild(channel) => Some(channel),
+            _ => None,
+        })
+        .find(|channel| channel.id.0 == reference.id)
+        .or_else(|| {
+            guild
+                .channels
+                .values()
+                .filter_map(|channel| match channel {
+                    Channel::Guild(channel) => Some(channel),
+                    _ => None,
+                })
+                .find(|channel| channel.name == reference.name)
+        })
+        .map(|channel| Reference {
+            id: channel.id.0,
+            name: channel.name.clone(),
+        })
+}
+
+/// Finds role of guild by id, or by name if there is no role with such id.
+///
+fn resolve_role(guild: &Guild, reference: &Reference) -> Option<Reference> {
+    guild
+        .roles
+        .get(&RoleId(reference.id))
+        .or_else(|| {
+            guild
+                .roles
+                .values()
+                .find(|role| role.name == reference.name)
+        })
+        .map(|role| Reference {
+            id: role.id.0,
+            name: role.name.clone(),
+        })
+}
+
+/// Resolves every channel and role of document against given guild and validates document.
+///
+/// Returns resolved document and descriptions of everything that could not be resolved.
+///
+pub fn resolve_settings(
+    guild: &Guild,
+    document: &SettingsDocument,
+) -> (SettingsDocument, Vec<String>) {
+    let mut errors: Vec<String> = Vec::new();
+    if document.version != DOCUMENT_VERSION {
+        errors.push(format!(
+            "Unsupported document version {} (expected {})",
+            document.version, DOCUMENT_VERSION
+        ));
+    }
+
+    let mut channel = |key: &str, reference: &Option<Reference>| {
+        let reference: &Reference = reference.as_ref()?;
+        let resolved: Option<Reference> = resolve_channel(guild, reference);
+        if resolved.is_none() {
+            errors.push(format!(
+                "{}: channel #{} ({}) was not found",
+                key, reference.name, reference.id
+            ));
+        }
+        resolved
+    };
+    let log_channel: Option<Reference> = channel("log_channel", &document.log_channel);
+    let moderation_channel: Option<Reference> =
+        channel("moderation_channel", &document.moderation_channel);
+    let music_order_channel: Option<Reference> =
+        channel("music_order_channel", &document.music_order_channel);
+    let music_log_channel: Option<Reference> =
+        channel("music_log_channel", &document.music_log_channel);
+    let mut log_routes: BTreeMap<String, Reference> = BTreeMap::new();
+    for (category, reference) in document.log_routes.iter() {
+        if let Some(reference) = channel(category, &Some(reference.clone())) {
+            log_routes.insert(category.clone(), reference);
+        }
+    }
+
+    let mut role = |key: &str, reference: &Option<Reference>| {
+        let reference: &Reference = reference.as_ref()?;
+        let resolved: Option<Reference> = resolve_role(guild, reference);
+        if resolved.is_none() {
+            errors.push(format!(
+                "{}: role @{} ({}) was not found",
+                key, reference.name, reference.id
+            ));
+        }
+        resolved
+    };
+    let member_role: Option<Reference> = role("member_role", &document.member_role);
+    let raid: Option<RaidEntry> = document.raid.as_ref().map(|raid| RaidEntry {
+        quarantine_role: role("quarantine_role", &raid.quarantine_role),
+        ..raid.clone()
+    });
+
+    for category in document.log_routes.keys() {
+        if let Err(error) = LogCategory::from_str(category) {
+            errors.push(error);
+        }
+    }
+    for event in document.disabled_log_events.iter() {
+        if let Err(error) = AuditEvent::from_str(event) {
+            errors.push(error);
+        }
+    }
+    for rule in document.automod_rules.iter() {
+        if let Err(error) = AutomodRuleKind::from_str(&rule.kind) {
+            errors.push(error);
+        }
+        if let Err(error) = AutomodAction::from_str(&rule.action) {
+            errors.push(error);
+        }
+    }
+
+    if document
+        .prefix
+        .as_ref()
+        .is_some_and(|value| value.chars().count() > prefix::MAX_PREFIX_LENGTH)
+    {
+        errors.push(format!(
+            "Prefix should be at most {} characters long",
+            prefix::MAX_PREFIX_LENGTH
+        ));
+    }
+
+    if let Some(Err(error)) = document.language.as_deref().map(Language::from_str) {
+        errors.push(error);
+    }
+
+    let resolved: SettingsDocument = SettingsDocument {
+        version: DOCUMENT_VERSION,
+        prefix: document.prefix.clone(),
+        language: document.language.clone(),
+        aliases: document.aliases.clone(),
+        log_channel,
+        moderation_channel,
+        music_order_channel,
+        music_log_channel,
+        member_role,
+        disabled_log_events: AuditEvent::ALL
+            .iter()
+            .map(|event| event.as_str().to_string())
+            .filter(|event| document.disabled_log_events.contains(event))
+            .collect::<Vec<String>>(),
+        log_routes,
+        automod_rules: document.automod_rules.clone(),
+        raid,
+        music_bots: document.music_bots.clone(),
+    };
+    (resolved, errors)
+}
+
+/// Describes differences between current and new settings documents.
+///
+pub fn diff_settings(current: &SettingsDocument, new: &SettingsDocument) -> Vec<String> {
+    let channel = |reference: &Option<Reference>| {
+        reference
+            .as_ref()
+            .map_or(String::from("not set"), |reference| {
+                format!("<#{}>", reference.id)
+            })
+    };
+    let role = |reference: &Option<Reference>| {
+        reference
+            .as_ref()
+            .map_or(String::from("not set"), |reference| {
+                format!("<@&{}>", reference.id)
+            })
+    };
+    let id = |reference: &Option<Reference>| reference.as_ref().map(|reference| reference.id);
+
+    let mut lines: Vec<String> = Vec::new();
+    if current.prefix != new.prefix {
+        lines.push(format!(
+            "prefix: {:?} → {:?}",
+            current.prefix.as_deref().unwrap_or("default"),
+            new.prefix.as_deref().unwrap_or("default")
+        ));
+    }
+    if current.language != new.language {
+        lines.push(format!(
+            "language: {} → {}",
+            current.language.as_deref().unwrap_or("default"),
+            new.language.as_deref().unwrap_or("default")
+        ));
+    }
+    if current.aliases != new.aliases {
+        lines.push(format!(
+            "command aliases: {} → {} aliases",
+            current.aliases.len(),
+            new.aliases.len()
+        ));
+    }
+    for (key, current, new) in [
+        ("log_channel", &current.log_channel, &new.log_channel),
+        (
+            "moderation_channel",
+            &current.moderation_channel,
+            &new.moderation_channel,
+        ),
+        (
+            "music_order_channel",
+            &current.music_order_channel,
+            &new.music_order_channel,
+        ),
+        (
+            "music_log_channel",
+            &current.music_log_channel,
+            &new.music_log_channel,
+        ),
+    ] {
+        if id(current) != id(new) {
+            lines.push(format!("{}: {} → {}", key, channel(current), channel(new)));
+        }
+    }
+    if id(&current.member_role) != id(&new.member_role) {
+        lines.push(format!(
+            "member_role: {} → {}",
+            role(&current.member_role),
+            role(&new.member_role)
+        ));
+    }
+    for category in LogCategory::ALL {
+        let current: Option<Reference> = current.log_routes.get(category.as_str()).cloned();
+        let new: Option<Reference> = new.log_routes.get(category.as_str()).cloned();
+        if id(&current) != id(&new) {
+            lines.push(format!(
+                "{} log channel: {} → {}",
+                category.as_str(),
+                channel(&current),
+                channel(&new)
+            ));
+        }
+    }
+    if current.disabled_log_events != new.disabled_log_events {
+        lines.push(format!(
+            "disabled log events: [{}] → [{}]",
+            current.disabled_log_events.join(", "),
+            new.disabled_log_events.join(", ")
+        ));
+    }
+    if current.automod_rules != new.automod_rules {
+        lines.push(format!(
+            "automod rules: {} → {} rules",
+            current.automod_rules.len(),
+            new.automod_rules.len()
+        ));
+    }
+    if current.raid != new.raid {
+        let raid = |raid: &Option<RaidEntry>| match raid {
+            Some(raid) => format!(
+                "threshold {:?}/{:?}s, minimal account age {:?} days, quarantine role {}",
+                raid.join_threshold,
+                raid.join_period,
+                raid.min_account_age,
+                role(&raid.quarantine_role)
+            ),
+            None => String::from("not configured"),
+        };
+        lines.push(format!(
+            "raid: {} → {}",
+            raid(&current.raid),
+            raid(&new.raid)
+        ));
+    }
+    for prefix in new.music_bots.iter() {
+        if !current.music_bots.contains(prefix) {
+            lines.push(format!(
+                "music bot '{}' is not on this server (invite it manually)",
+                prefix
+            ));
+        }
+    }
+    lines
+}
+
+/// Writes resolved settings document into database.
+///
+async fn apply_settings(
+    transaction: &mut Transaction<'_, Any>,
+    guild_id: GuildId,
+    document: &SettingsDocument,
+) -> sqlx::Result<()> {
+    let _timer: metrics::Timer = metrics::Timer::query("settings.import");
+    let channels: Vec<u64> = [
+        &document.log_channel,
+        &document.moderation_channel,
+        &document.music_order_channel,
+        &document.music_log_channel,
+    ]
+    .into_iter()
+    .flatten()
+    .chain(document.log_routes.values())
+    .map(|reference| reference.id)
+    .collect::<Vec<u64>>();
+    for channel_id in channels {
+        sqlx::query("INSERT INTO channels VALUES ($1, $2) ON CONFLICT DO NOTHING")
+            .bind(channel_id.db_id())
+            .bind(guild_id.db_id())
+            .execute(&mut **transaction)
+            .await?;
+    }
+    if let Some(member_role) = document.member_role.as_ref() {
+        sqlx::query("DELETE FROM roles WHERE discord_id = $1 AND guild_id = $2")
+            .bind(member_role.id.db_id())
+            .bind(guild_id.db_id())
+            .execute(&mut **transaction)
+            .await?;
+        sqlx::query("INSERT INTO roles VALUES ($1, $2)")
+            .bind(member_role.id.db_id())
+            .bind(guild_id.db_id())
+            .execute(&mut **transaction)
+            .await?;
+    }
+
+    let id =
+        |reference: &Option<Reference>| reference.as_ref().map(|reference| reference.id.db_id());
+    let disabled_log_events: i64 = document
+        .disabled_log_events
+        .iter()
+        .filter_map(|event| AuditEvent::from_str(event).ok())
+        .fold(0, |bits, event| bits | event.bit());
+    sqlx::query(
+        "
+        UPDATE settings SET prefix = $1, language = $2, log_channel_id = $3, moderation_channel_id = $4, music_order_channel_id = $5, music_log_channel_id = $6, member_role_id = $7, disabled_log_events = $8
+        WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = $9)
+    ",
+    )
+    .bind(&document.prefix)
+    .bind(&document.language)
+    .bind(id(&document.log_channel))
+    .bind(id(&document.moderation_channel))
+    .bind(id(&document.music_order_channel))
+    .bind(id(&document.music_log_channel))
+    .bind(id(&document.member_role))
+    .bind(disabled_log_events)
+    .bind(guild_id.db_id())
+    .execute(&mut **transaction)
+    .await?;
+
+    sqlx::query("DELETE FROM log_routes WHERE guild_id = $1")
+        .bind(guild_id.db_id())
+        .execute(&mut **transaction)
+        .await?;
+    for (category, reference) in document.log_routes.iter() {
+        sqlx::query("INSERT INTO log_routes VALUES ($1, $2, $3)")
+            .bind(guild_id.db_id())
+            .bind(category)
+            .bind(reference.id.db_id())
+            .execute(&mut **transaction)
+            .await?;
+    }
+
+    sqlx::query("DELETE FROM command_aliases WHERE guild_id = $1")
+        .bind(guild_id.db_id())
+        .execute(&mut **transaction)
+        .await?;
+    for (alias, command) in document.aliases.iter() {
+        sqlx::query("INSERT INTO command_aliases VALUES ($1, $2, $3)")
+            .bind(guild_id.db_id())
+            .bind(alias)
+            .bind(command)
+            .execute(&mut **transaction)
+            .await?;
+    }
+
+    sqlx::query("DELETE FROM automod_rules WHERE guild_id = $1")
+        .bind(guild_id.db_id())
+        .execute(&mut **transaction)
+        .await?;
+    for rule in document.automod_rules.iter() {
+        sqlx::query("INSERT INTO automod_rules VALUES ($1, $2, $3, $4, $5, $6)")
+            .bind(guild_id.db_id())
+            .bind(&rule.kind)
+            .bind(rule.threshold)
+            .bind(rule.period)
+            .bind(&rule.pattern)
+            .bind(&rule.action)
+            .execute(&mut **transaction)
+            .await?;
+    }
+
+    if document.raid.is_some() {
+        sqlx::query("INSERT INTO raid_settings (guild_id) VALUES ($1) ON CONFLICT DO NOTHING")
+            .bind(guild_id.db_id())
+            .execute(&mut **transaction)
+            .await?;
+    }
+    let raid: RaidEntry = document.raid.clone().unwrap_or_default();
+    sqlx::query(
+        "UPDATE raid_settings SET join_threshold = $1, join_period = $2, min_account_age = $3, quarantine_role_id = $4 WHERE guild_id = $5",
+    )
+    .bind(raid.join_threshold)
+    .bind(raid.join_period)
+    .bind(raid.min_account_age)
+    .bind(id(&raid.quarantine_role))
+    .bind(guild_id.db_id())
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
+
+/// Reads settings document either from attachment or from message text.
+///
+async fn read_document(
+    language: Language,
+    message: &Message,
+    args: &Args,
+) -> Result<SettingsDocument, String> {
+    let text: String = match message.attachments.first() {
+        Some(attachment) => {
+            let bytes: Vec<u8> = attachment
+                .download()
+                .await
+                .map_err(|error| tr(language, "transfer.download_failed", &[("error", &error)]))?;
+            String::from_utf8(bytes).map_err(|_| tr(language, "transfer.not_a_text_file", &[]))?
+        }
+        None => args
+            .rest()
+            .trim()
+            .trim_start_matches("```json")
+            .trim_matches('`')
+            .to_string(),
+    };
+    if text.trim().is_empty() {
+        return Err(tr(language, "transfer.no_document", &[]));
+    }
+    serde_json::from_str::<SettingsDocument>(&text)
+        .map_err(|error| tr(language, "transfer.invalid_document", &[("error", &error)]))
+}
+
+#[command]
+#[num_args(0)]
+pub async fn export(ctx: &Context, message: &Message, _: Args) -> CommandResult {
+    let guild: Guild = match message.guild(&ctx.cache) {
+        Some(guild) => guild,
+        None => return Ok(()),
+    };
+    let language: Language = locale::user_language(Some(guild.id), message.author.id).await;
+
+    let document: SettingsDocument = export_settings(&guild).await;
+    let json: String = serde_json::to_string_pretty(&document)?;
+    message
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.content(tr(language, "transfer.exported", &[("guild", &guild.name)]))
+                .add_file(AttachmentType::Bytes {
+                    data: Cow::from(json.into_bytes()),
+                    filename: format!("settings-{}.json", guild.id),
+                })
+        })
+        .await?;
+
+    logger::log(log::Level::Info, "settings export was called");
+    Ok(())
+}
+
+#[command]
+#[usage("[apply] [document]")]
+#[example("apply")]
+pub async fn import(ctx: &Context, message: &Message, mut args: Args) -> CommandResult {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should already be established at this moment");
+
+    let guild: Guild = match message.guild(&ctx.cache) {
+        Some(guild) => guild,
+        None => return Ok(()),
+    };
+    let language: Language = locale::user_language(Some(guild.id), message.author.id).await;
+    let is_apply: bool = args.current() == Some("apply");
+    if is_apply {
+        let _ = args.advance();
+    }
+    let document: SettingsDocument = match read_document(language, message, &args).await {
+        Ok(document) => document,
+        Err(error) => {
+            reply::warning(ctx, message, &error).await?;
+            return Ok(());
+        }
+    };
+
+    let (resolved, errors) = resolve_settings(&guild, &document);
+    let changes: Vec<String> = diff_settings(&export_settings(&guild).await, &resolved);
+    let mut kind: ReplyKind = ReplyKind::Info;
+    let mut reply: String = if changes.is_empty() {
+        tr(language, "transfer.up_to_date", &[])
+    } else {
+        tr(
+            language,
+            "transfer.changes",
+            &[("count", &changes.len()), ("changes", &changes.join("\n"))],
+        )
+    };
+    if !errors.is_empty() {
+        kind = ReplyKind::Warning;
+        reply.push_str("\n\n");
+        reply.push_str(&tr(
+            language,
+            "transfer.problems",
+            &[("count", &errors.len()), ("problems", &errors.join("\n"))],
+        ));
+    }
+
+    reply.push_str("\n\n");
+    if !is_apply {
+        reply.push_str(&tr(
+            language,
+            "transfer.dry_run",
+            &[("prefix", &prefix::get_prefix(Some(guild.id)).await)],
+        ));
+    } else if !errors.is_empty() {
+        reply.push_str(&tr(language, "transfer.not_applied", &[]));
+    } else if !changes.is_empty() {
+        let mut transaction: Transaction<'_, Any> = connection.begin().await?;
+        match apply_settings(&mut transaction, guild.id, &resolved).await {
+            Ok(()) => {
+                transaction.commit().await?;
+                cache::invalidate(guild.id);
+                prefix::invalidate(guild.id);
+                automod::invalidate(guild.id);
+                kind = ReplyKind::Success;
+                reply.push_str(&tr(language, "transfer.imported", &[]));
+            }
+            Err(error) => {
+                transaction.rollback().await?;
+                kind = ReplyKind::Error;
+                reply.push_str(&tr(
+                    language,
+                    "transfer.import_failed",
+                    &[("error", &error)],
+                ));
+            }
+        }
+    }
+    reply::send(ctx, message, kind, &reply).await?;
+
+    logger::log(log::Level::Info, "settings import was called");
+    Ok(())
+}
@@ -0,0 +1,154 @@
+//! `shutdown` module implements coordinated shutdown of bots: commands stop being accepted,
+//! music bots save their queues and leave voice channels, shards are disconnected
+//! and pending database writes and logs are flushed.
+//!
+
+use crate::{
+    bot::{music_playing, BotHandle},
+    db::{
+        cache,
+        connections::SERVERS_DB,
+        models::{Id, MusicBot},
+        music_bots,
+    },
+    logger,
+};
+use serenity::{client::bridge::gateway::ShardManager, model::id::GuildId, prelude::Mutex};
+use sqlx::AnyPool;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::task::JoinHandle;
+
+/// Whether shutdown was started.
+///
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// Returns whether shutdown was started, in which case bots should not start anything new.
+///
+pub fn is_shutting_down() -> bool {
+    SHUTTING_DOWN.load(Ordering::SeqCst)
+}
+
+/// Returns time that shutdown is allowed to take (`SHUTDOWN_TIMEOUT_SECONDS`, 15 seconds by default).
+///
+fn timeout() -> Duration {
+    Duration::from_secs(
+        dotenv::var("SHUTDOWN_TIMEOUT_SECONDS")
+            .ok()
+            .and_then(|seconds| seconds.parse::<u64>().ok())
+            .unwrap_or(15),
+    )
+}
+
+/// Waits until process receives SIGINT (Ctrl+C) or SIGTERM.
+///
+pub async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate: tokio::signal::unix::Signal =
+            signal(SignalKind::terminate()).expect("SIGTERM handler should be installed");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// Saves queues of music bot and leaves every voice channel it is in.
+///
+/// Voice channels are forgotten the same way `leave` command does it, so that bot is able
+/// to join them again after restart.
+///
+async fn leave_voice(bot: &BotHandle) {
+    let songbird: &songbird::Songbird = match &bot.songbird {
+        Some(songbird) => songbird,
+        None => return,
+    };
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    let music_bots: Vec<MusicBot> = sqlx::query_as::<_, MusicBot>(
+        "SELECT * FROM music_bots WHERE prefix = $1 AND on_channel_id IS NOT NULL",
+    )
+    .bind(bot.prefix.clone())
+    .fetch_all(connection)
+    .await
+    .expect("Query should be correct");
+    for music_bot in music_bots {
+        let guild_id: GuildId = music_bot.guild_id.into();
+        music_playing::save_queue(songbird, guild_id, &bot.prefix).await;
+        if let Err(error) = songbird.remove(guild_id).await {
+            logger::log(
+                log::Level::Warn,
+                &format!(
+                    "Unable to leave voice channel in '{}' guild: {}",
+                    guild_id, error
+                ),
+            );
+        }
+        if let Some(channel_id) = music_bot.on_channel_id.0 {
+            music_bots::leave(connection, Id(channel_id))
+                .await
+                .expect("Query should be correct");
+            // Settings that were referencing deleted channel are reset by foreign key.
+            cache::invalidate(guild_id);
+        }
+    }
+    sqlx::query("UPDATE music_bots SET on_channel_id = NULL WHERE prefix = $1")
+        .bind(bot.prefix.clone())
+        .execute(connection)
+        .await
+        .expect("Query should be correct");
+}
+
+/// Shuts bots down and waits for their tasks to finish, giving up after timeout.
+///
+pub async fn shutdown(bots: Vec<BotHandle>, tasks: Vec<JoinHandle<()>>) {
+    if SHUTTING_DOWN.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    logger::log(log::Level::Info, "Shutting down");
+
+    let graceful = async {
+        for bot in &bots {
+            leave_voice(bot).await;
+        }
+        for bot in &bots {
+            let shard_manager: Option<Arc<Mutex<ShardManager>>> = bot
+                .shard_manager
+                .lock()
+                .expect("Lock should not be poisoned")
+                .clone();
+            if let Some(shard_manager) = shard_manager {
+                shard_manager.lock().await.shutdown_all().await;
+            }
+        }
+        for task in tasks {
+            let _ = task.await;
+        }
+        logger::reporter::flush().await;
+        if let Some(connection) = SERVERS_DB.get() {
+            connection.close().await;
+        }
+    };
+    match tokio::time::timeout(timeout(), graceful).await {
+        Ok(()) => logger::log(log::Level::Info, "Shut down gracefully"),
+        Err(_) => logger::log(
+            log::Level::Warn,
+            "Shutdown timed out, remaining work was dropped",
+        ),
+    }
+    log::logger().flush();
+}
@@ -0,0 +1,187 @@
+//! `supervisor` module implements supervisor that restarts crashed bots with exponential backoff
+//! and keeps track of their status.
+//!
+
+use crate::{
+    bot::{shutdown, Bot},
+    logger,
+};
+use serenity::{gateway::GatewayError, http::HttpError, Error};
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+/// Delay before first restart of crashed bot.
+///
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Maximal delay between two restarts of crashed bot.
+///
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+/// Bot that ran for that long before crash is considered stable, so its backoff is reset.
+///
+const STABLE_PERIOD: Duration = Duration::from_secs(10 * 60);
+
+/// Statuses of supervised bots.
+///
+static STATUSES: OnceLock<Mutex<HashMap<&'static str, BotStatus>>> = OnceLock::new();
+
+/// `BotState` enum represents state of supervised bot.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BotState {
+    /// Bot client is running.
+    ///
+    Running,
+    /// Bot crashed and is waiting to be restarted.
+    ///
+    Restarting,
+    /// Bot stopped because of error that restarts would not fix (e.g. invalid token).
+    ///
+    Failed,
+    /// Bot was shut down.
+    ///
+    Stopped,
+}
+impl BotState {
+    /// Returns name of state.
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BotState::Running => "running",
+            BotState::Restarting => "restarting",
+            BotState::Failed => "failed",
+            BotState::Stopped => "stopped",
+        }
+    }
+}
+
+/// `BotStatus` struct represents status of supervised bot.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct BotStatus {
+    /// Current state of bot.
+    ///
+    pub state: BotState,
+    /// Number of times bot was restarted.
+    ///
+    pub restarts: u32,
+}
+
+/// Records status of bot.
+///
+fn set_status(name: &'static str, state: BotState, restarts: u32) {
+    let _ = STATUSES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned")
+        .insert(name, BotStatus { state, restarts });
+}
+
+/// Returns statuses of all supervised bots, sorted by their names.
+///
+pub fn statuses() -> Vec<(&'static str, BotStatus)> {
+    let mut statuses: Vec<(&'static str, BotStatus)> = STATUSES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned")
+        .iter()
+        .map(|(name, status)| (*name, *status))
+        .collect::<Vec<(&'static str, BotStatus)>>();
+    statuses.sort_by_key(|(name, _)| *name);
+    statuses
+}
+
+/// Returns whether error can not be fixed by restarting bot.
+///
+fn is_fatal(error: &Error) -> bool {
+    match error {
+        Error::Gateway(
+            GatewayError::InvalidAuthentication
+            | GatewayError::DisallowedGatewayIntents
+            | GatewayError::InvalidGatewayIntents,
+        ) => true,
+        Error::Http(error) => matches!(
+            error.as_ref(),
+            HttpError::UnsuccessfulRequest(response) if response.status_code.as_u16() == 401
+        ),
+        _ => false,
+    }
+}
+
+/// Sleeps for given duration, waking up early if shutdown was started.
+///
+async fn sleep(duration: Duration) {
+    let deadline: Instant = Instant::now() + duration;
+    while !shutdown::is_shutting_down() {
+        let remaining: Duration = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        tokio::time::sleep(remaining.min(Duration::from_secs(1))).await;
+    }
+}
+
+/// Runs bot and restarts it whenever it crashes, until shutdown or fatal error.
+///
+pub async fn supervise(bot: Bot) {
+    let name: &'static str = bot.name();
+    if !bot.check_intents().await {
+        set_status(name, BotState::Failed, 0);
+        return;
+    }
+
+    let mut backoff: Duration = INITIAL_BACKOFF;
+    let mut restarts: u32 = 0;
+    loop {
+        set_status(name, BotState::Running, restarts);
+        let started: Instant = Instant::now();
+        let result: serenity::Result<()> = bot.run().await;
+        if shutdown::is_shutting_down() {
+            set_status(name, BotState::Stopped, restarts);
+            return;
+        }
+
+        match result {
+            Err(error) if is_fatal(&error) => {
+                set_status(name, BotState::Failed, restarts);
+                logger::log(
+                    log::Level::Error,
+                    &format!(
+                        "{} stopped because of error that restart would not fix: {:?}",
+                        name, error
+                    ),
+                );
+                return;
+            }
+            Err(error) => logger::log(
+                log::Level::Error,
+                &format!("An error occurred while running the {}: {:?}", name, error),
+            ),
+            Ok(()) => logger::log(log::Level::Warn, &format!("{} stopped unexpectedly", name)),
+        }
+        if started.elapsed() >= STABLE_PERIOD {
+            backoff = INITIAL_BACKOFF;
+        }
+        restarts += 1;
+        set_status(name, BotState::Restarting, restarts);
+        logger::log(
+            log::Level::Warn,
+            &format!(
+                "Restarting {} in {}s (restart #{})",
+                name,
+                backoff.as_secs(),
+                restarts
+            ),
+        );
+        sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+        if shutdown::is_shutting_down() {
+            set_status(name, BotState::Stopped, restarts);
+            return;
+        }
+    }
+}
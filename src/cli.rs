@@ -0,0 +1,387 @@
+//! `cli` module implements command-line interface of `warden` binary, which allows operators
+//! to inspect and fix state of database without Discord.
+//!
+
+use crate::{
+    bot::{self, settings::RESETTABLE_SETTINGS},
+    db::{
+        self, aliases,
+        connections::{Backend, SERVERS_BACKEND, SERVERS_DB},
+        models::{CommandAlias, DbId, Id, Language, LogRoute, MusicBot, Setting},
+    },
+};
+use sqlx::{any::AnyArguments, query::Query, Any, AnyPool};
+use std::{path::PathBuf, str::FromStr};
+
+/// Usage of `warden` binary.
+///
+pub const USAGE: &str = "Usage: warden [command]
+
+Commands:
+    run                                   Start all bots (default)
+    migrate                               Apply pending database migrations
+    db check                              Check integrity and schema version of database
+    restore <file>                        Replace SQLite database with backup (bots should be stopped)
+    guild list                            List registered guilds
+    guild show <guild>                    Show settings of guild
+    settings set <guild> <key> <value>    Change setting of guild (`none` resets it)";
+
+/// `Command` enum represents subcommand of `warden` binary.
+///
+#[derive(Clone, Debug)]
+pub enum Command {
+    /// Starts all bots.
+    ///
+    Run,
+    /// Applies pending migrations.
+    ///
+    Migrate,
+    /// Checks integrity and schema version of database.
+    ///
+    DbCheck,
+    /// Replaces `SQLite` database with backup.
+    ///
+    Restore(PathBuf),
+    /// Lists registered guilds.
+    ///
+    GuildList,
+    /// Shows settings of guild.
+    ///
+    GuildShow(Id),
+    /// Changes setting of guild.
+    ///
+    SettingsSet {
+        guild_id: Id,
+        key: String,
+        value: String,
+    },
+}
+impl Command {
+    /// Parses command from arguments of binary (without binary name).
+    ///
+    /// Returns usage as error if arguments are incorrect.
+    ///
+    pub fn parse(args: &[String]) -> Result<Command, String> {
+        let parse_id = |id: &str| Id::from_str(id).map_err(|_| format!("'{}' is not an id", id));
+
+        let args: Vec<&str> = args.iter().map(String::as_str).collect::<Vec<&str>>();
+        match args.as_slice() {
+            [] | ["run"] => Ok(Command::Run),
+            ["migrate"] => Ok(Command::Migrate),
+            ["db", "check"] => Ok(Command::DbCheck),
+            ["restore", file] => Ok(Command::Restore(PathBuf::from(file))),
+            ["guild", "list"] => Ok(Command::GuildList),
+            ["guild", "show", guild_id] => Ok(Command::GuildShow(parse_id(guild_id)?)),
+            ["settings", "set", guild_id, key, value] => Ok(Command::SettingsSet {
+                guild_id: parse_id(guild_id)?,
+                key: key.to_string(),
+                value: value.to_string(),
+            }),
+            _ => Err(USAGE.to_string()),
+        }
+    }
+}
+
+/// Executes administrative command (every command except `Command::Run`).
+///
+pub async fn execute(command: Command) -> Result<(), String> {
+    match command {
+        Command::Run => Err("Bots are started by main".to_string()),
+        Command::Migrate => migrate().await,
+        Command::DbCheck => check().await,
+        Command::Restore(file) => db::backup::restore(&file).await,
+        Command::GuildList => list_guilds().await,
+        Command::GuildShow(guild_id) => show_guild(guild_id).await,
+        Command::SettingsSet {
+            guild_id,
+            key,
+            value,
+        } => set_setting(guild_id, &key, &value).await,
+    }
+}
+
+/// Returns backend and connection to servers database.
+///
+fn connection() -> (Backend, &'static AnyPool) {
+    (
+        *SERVERS_BACKEND
+            .get()
+            .expect("Backend should be set at this moment"),
+        SERVERS_DB
+            .get()
+            .expect("Connection should be established at this moment"),
+    )
+}
+
+/// Returns versions of successfully applied migrations (empty if database was never migrated).
+///
+async fn applied_migrations(connection: &AnyPool) -> Vec<i64> {
+    sqlx::query_as::<_, (i64,)>(
+        "SELECT version FROM _sqlx_migrations WHERE success ORDER BY version",
+    )
+    .fetch_all(connection)
+    .await
+    .map(|versions| versions.into_iter().map(|(version,)| version).collect())
+    .unwrap_or_default()
+}
+
+/// Applies pending migrations.
+///
+async fn migrate() -> Result<(), String> {
+    db::connect_db().await;
+    let (backend, connection) = connection();
+
+    let before: usize = applied_migrations(connection).await.len();
+    db::migrator(backend)
+        .run(connection)
+        .await
+        .map_err(|error| format!("Migrations were not applied: {}", error))?;
+    let applied: Vec<i64> = applied_migrations(connection).await;
+    println!(
+        "Applied {} migrations, schema version is {}",
+        applied.len() - before,
+        applied.last().copied().unwrap_or_default()
+    );
+    Ok(())
+}
+
+/// Checks integrity and schema version of database.
+///
+async fn check() -> Result<(), String> {
+    db::connect_db().await;
+    let (backend, connection) = connection();
+
+    let mut problems: Vec<String> = Vec::new();
+    if backend == Backend::Sqlite {
+        let (integrity,): (String,) = sqlx::query_as("PRAGMA integrity_check")
+            .fetch_one(connection)
+            .await
+            .map_err(|error| format!("Unable to check integrity: {}", error))?;
+        if integrity != "ok" {
+            problems.push(format!("Integrity check failed: {}", integrity));
+        }
+        let violations: usize = sqlx::query("PRAGMA foreign_key_check")
+            .fetch_all(connection)
+            .await
+            .map_err(|error| format!("Unable to check foreign keys: {}", error))?
+            .len();
+        if violations != 0 {
+            problems.push(format!("{} foreign key violations", violations));
+        }
+    }
+
+    let applied: Vec<i64> = applied_migrations(connection).await;
+    for version in &applied {
+        if !db::migrator(backend)
+            .iter()
+            .any(|migration| migration.version == *version)
+        {
+            problems.push(format!("Migration {} is unknown to this build", version));
+        }
+    }
+    let pending: Vec<i64> = db::migrator(backend)
+        .iter()
+        .map(|migration| migration.version)
+        .filter(|version| !applied.contains(version))
+        .collect::<Vec<i64>>();
+    if !pending.is_empty() {
+        problems.push(format!(
+            "{} migrations are pending, run `warden migrate`",
+            pending.len()
+        ));
+    }
+
+    println!(
+        "Backend: {}\nSchema version: {}",
+        backend.as_str(),
+        applied.last().copied().unwrap_or_default()
+    );
+    if problems.is_empty() {
+        println!("Database is healthy");
+        return Ok(());
+    }
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    Err(format!("Database check found {} problems", problems.len()))
+}
+
+/// Lists registered guilds.
+///
+async fn list_guilds() -> Result<(), String> {
+    db::connect_db().await;
+    let (_, connection) = connection();
+
+    let guilds: Vec<(i64, Option<String>, Option<String>)> = sqlx::query_as(
+        "
+        SELECT guilds.discord_id, settings.prefix, settings.language FROM guilds
+        JOIN settings ON settings.id = guilds.settings_id
+        ORDER BY guilds.discord_id
+    ",
+    )
+    .fetch_all(connection)
+    .await
+    .map_err(|error| format!("Unable to list guilds: {}", error))?;
+    for (guild_id, prefix, language) in &guilds {
+        println!(
+            "{}\tprefix: {}\tlanguage: {}",
+            guild_id,
+            prefix.as_deref().unwrap_or(crate::MAIN_BOT_PREFIX),
+            language.as_deref().unwrap_or(Language::default().as_str())
+        );
+    }
+    println!("{} guilds are registered", guilds.len());
+    Ok(())
+}
+
+/// Shows settings of guild.
+///
+async fn show_guild(guild_id: Id) -> Result<(), String> {
+    db::connect_db().await;
+    let (_, connection) = connection();
+
+    let setting: Setting = db::cache::load(guild_id.into())
+        .await
+        .ok_or_else(|| format!("Guild {} is not registered", guild_id.0))?;
+    let show = |id: Option<u64>| id.map_or("none".to_string(), |id| id.to_string());
+    println!("Guild {}", guild_id.0);
+    println!(
+        "prefix: {}",
+        setting.prefix.as_deref().unwrap_or(crate::MAIN_BOT_PREFIX)
+    );
+    println!(
+        "language: {}",
+        setting
+            .language
+            .as_deref()
+            .unwrap_or(Language::default().as_str())
+    );
+    println!("log_channel: {}", show(setting.log_channel_id.0));
+    println!(
+        "moderation_channel: {}",
+        show(setting.moderation_channel_id.0)
+    );
+    println!(
+        "music_order_channel: {}",
+        show(setting.music_order_channel_id.0)
+    );
+    println!(
+        "music_log_channel: {}",
+        show(setting.music_log_channel_id.0)
+    );
+    println!("member_role: {}", show(setting.member_role_id.0));
+    println!("disabled_log_events: {:#b}", setting.disabled_log_events);
+
+    let log_routes: Vec<LogRoute> =
+        sqlx::query_as::<_, LogRoute>("SELECT * FROM log_routes WHERE guild_id = $1")
+            .bind(guild_id.db_id())
+            .fetch_all(connection)
+            .await
+            .map_err(|error| format!("Unable to read log routes: {}", error))?;
+    for route in log_routes {
+        println!(
+            "log route {}: {}",
+            route.category.as_str(),
+            route.channel_id.0
+        );
+    }
+    let aliases: Vec<CommandAlias> = aliases::list(connection, guild_id)
+        .await
+        .map_err(|error| format!("Unable to read aliases: {}", error))?;
+    for alias in aliases {
+        println!("alias {}: {}", alias.alias, alias.command);
+    }
+    let music_bots: Vec<MusicBot> =
+        sqlx::query_as::<_, MusicBot>("SELECT * FROM music_bots WHERE guild_id = $1")
+            .bind(guild_id.db_id())
+            .fetch_all(connection)
+            .await
+            .map_err(|error| format!("Unable to read music bots: {}", error))?;
+    for music_bot in music_bots {
+        println!(
+            "{}: {}",
+            music_bot.prefix.trim(),
+            music_bot
+                .on_channel_id
+                .0
+                .map_or("idle".to_string(), |id| format!("in {}", id))
+        );
+    }
+    Ok(())
+}
+
+/// Changes setting of guild, `none` value resets it.
+///
+/// Running bots see the change after their settings cache is refreshed (e.g. on restart).
+///
+async fn set_setting(guild_id: Id, key: &str, value: &str) -> Result<(), String> {
+    db::connect_db().await;
+    let (_, connection) = connection();
+
+    let column: &str = RESETTABLE_SETTINGS
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, column)| *column)
+        .ok_or_else(|| {
+            format!(
+                "Unknown setting '{}', expected one of: {}",
+                key,
+                RESETTABLE_SETTINGS
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            )
+        })?;
+    if sqlx::query("SELECT discord_id FROM guilds WHERE discord_id = $1")
+        .bind(guild_id.db_id())
+        .fetch_optional(connection)
+        .await
+        .map_err(|error| format!("Unable to find guild: {}", error))?
+        .is_none()
+    {
+        return Err(format!("Guild {} is not registered", guild_id.0));
+    }
+
+    let query: String = format!(
+        "UPDATE settings SET {} = $1 WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = $2)",
+        column
+    );
+    let update: Query<'_, Any, AnyArguments<'_>> = sqlx::query(&query);
+    let update: Query<'_, Any, AnyArguments<'_>> = match (column, value) {
+        ("prefix" | "language", "none") => update.bind(None::<String>),
+        (_, "none") => update.bind(None::<i64>),
+        ("prefix", prefix) => {
+            if prefix.chars().count() > bot::prefix::MAX_PREFIX_LENGTH {
+                return Err(format!(
+                    "Prefix should be at most {} characters long",
+                    bot::prefix::MAX_PREFIX_LENGTH
+                ));
+            }
+            update.bind(prefix.to_string())
+        }
+        ("language", language) => update.bind(Language::from_str(language)?.to_string()),
+        (_, id) => {
+            let id: Id = Id::from_str(id).map_err(|_| format!("'{}' is not an id", id))?;
+            let reference: &str = if column == "member_role_id" {
+                "INSERT INTO roles SELECT $1, $2 WHERE NOT EXISTS (SELECT 1 FROM roles WHERE discord_id = $1 AND guild_id = $2)"
+            } else {
+                "INSERT INTO channels VALUES ($1, $2) ON CONFLICT (discord_id) DO NOTHING"
+            };
+            sqlx::query(reference)
+                .bind(id.db_id())
+                .bind(guild_id.db_id())
+                .execute(connection)
+                .await
+                .map_err(|error| format!("Unable to register {}: {}", id.0, error))?;
+            update.bind(id.db_id())
+        }
+    };
+    update
+        .bind(guild_id.db_id())
+        .execute(connection)
+        .await
+        .map_err(|error| format!("Unable to change {}: {}", key, error))?;
+    println!("{} of guild {} was set to {}", key, guild_id.0, value);
+    Ok(())
+}
@@ -1,19 +1,73 @@
 //! `db` module implements database interaction interface.
 //!
 
+pub mod aliases;
+pub mod backup;
+pub mod cache;
+pub mod cases;
 pub mod connections;
+pub mod guilds;
+pub mod jobs;
 pub mod models;
+pub mod music_bots;
 
-/// Initializes databases.
+use connections::Backend;
+use sqlx::migrate::Migrator;
+
+/// Migrations of `SQLite` database.
 ///
-pub async fn init_db() {
+pub static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("migrations/sqlite");
+
+/// Migrations of `PostgreSQL` database.
+///
+pub static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("migrations/postgres");
+
+/// Returns URL of servers database, which is either given directly by `SERVERS_DATABASE_URL`
+/// (e.g. `postgres://warden@localhost/warden`) or as a path to `SQLite` file by `SERVERS_DATABASE_FILE`.
+///
+pub fn servers_database_url() -> String {
+    dotenv::var("SERVERS_DATABASE_URL")
+        .or_else(|_| dotenv::var("SERVERS_DATABASE_FILE").map(|file| format!("sqlite://{}", file)))
+        .expect("SERVERS_DATABASE_URL or SERVERS_DATABASE_FILE should be provided")
+}
+
+/// Returns migrations of given backend.
+///
+pub fn migrator(backend: Backend) -> &'static Migrator {
+    match backend {
+        Backend::Sqlite => &SQLITE_MIGRATOR,
+        Backend::Postgres => &POSTGRES_MIGRATOR,
+    }
+}
+
+/// Connects to databases without applying migrations.
+///
+pub async fn connect_db() {
+    let url: String = servers_database_url();
+    let backend: Backend =
+        Backend::from_url(&url).expect("Database URL should be either sqlite or postgres one");
+
+    connections::SERVERS_BACKEND
+        .set(backend)
+        .expect("Backend should be set only once");
     connections::SERVERS_DB
-        .set(
-            connections::connect(
-                &dotenv::var("SERVERS_DATABASE_FILE")
-                    .expect("SERVERS_DATABASE_FILE should be provided"),
-            )
-            .await,
-        )
+        .set(connections::connect(&url).await)
         .expect("It should be possible to connect to SERVERS_DB");
 }
+
+/// Initializes databases.
+///
+pub async fn init_db() {
+    connect_db().await;
+
+    let backend: Backend = *connections::SERVERS_BACKEND
+        .get()
+        .expect("Backend was set above");
+    let connection: &sqlx::AnyPool = connections::SERVERS_DB
+        .get()
+        .expect("Connection was established above");
+    migrator(backend)
+        .run(connection)
+        .await
+        .expect("Migrations should be applied correctly");
+}
@@ -0,0 +1,58 @@
+//! `aliases` module implements queries of command aliases of guilds.
+//!
+
+use crate::db::models::{CommandAlias, DbId, Id};
+use sqlx::{any::AnyQueryResult, AnyPool};
+
+/// Returns command aliases of guild.
+///
+pub async fn list(connection: &AnyPool, guild_id: Id) -> sqlx::Result<Vec<CommandAlias>> {
+    sqlx::query_as::<_, CommandAlias>("SELECT * FROM command_aliases WHERE guild_id = $1")
+        .bind(guild_id.db_id())
+        .fetch_all(connection)
+        .await
+}
+
+/// Makes alias refer to given command, replacing command it referred to before.
+///
+pub async fn set(
+    connection: &AnyPool,
+    guild_id: Id,
+    alias: &str,
+    command: &str,
+) -> sqlx::Result<()> {
+    sqlx::query(
+        "
+        INSERT INTO command_aliases VALUES ($1, $2, $3)
+        ON CONFLICT (guild_id, alias) DO UPDATE SET command = excluded.command
+    ",
+    )
+    .bind(guild_id.db_id())
+    .bind(alias)
+    .bind(command)
+    .execute(connection)
+    .await?;
+    Ok(())
+}
+
+/// Removes alias of guild, returning whether it existed.
+///
+pub async fn remove(connection: &AnyPool, guild_id: Id, alias: &str) -> sqlx::Result<bool> {
+    let result: AnyQueryResult =
+        sqlx::query("DELETE FROM command_aliases WHERE guild_id = $1 AND alias = $2")
+            .bind(guild_id.db_id())
+            .bind(alias)
+            .execute(connection)
+            .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Removes every alias of guild.
+///
+pub async fn clear(connection: &AnyPool, guild_id: Id) -> sqlx::Result<()> {
+    sqlx::query("DELETE FROM command_aliases WHERE guild_id = $1")
+        .bind(guild_id.db_id())
+        .execute(connection)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,284 @@
+//! `backup` module implements online backups of `SQLite` database with rotation
+//! and restoring of database from one of them.
+//!
+//! Backups are made with `VACUUM INTO`, which copies consistent snapshot of database
+//! without blocking bots that keep using it. `PostgreSQL` deployments should use `pg_dump` instead.
+//!
+
+use crate::{
+    db::{
+        connections::{Backend, SERVERS_BACKEND, SERVERS_DB},
+        SQLITE_MIGRATOR,
+    },
+    logger,
+};
+use sqlx::{migrate::Migration, AnyConnection, AnyPool, Connection};
+use std::{
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Prefix of backup file names.
+///
+const FILE_PREFIX: &str = "servers-";
+
+/// Extension of backup file names.
+///
+const FILE_EXTENSION: &str = ".db";
+
+/// Whether backup loop was already started.
+///
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Returns directory where backups are stored (`BACKUP_DIRECTORY`, `backups` by default).
+///
+fn directory() -> PathBuf {
+    PathBuf::from(dotenv::var("BACKUP_DIRECTORY").unwrap_or_else(|_| "backups".to_string()))
+}
+
+/// Returns interval between two scheduled backups (`BACKUP_INTERVAL_HOURS`, 24 hours by default).
+///
+fn interval() -> Duration {
+    let hours: u64 = dotenv::var("BACKUP_INTERVAL_HOURS")
+        .ok()
+        .and_then(|hours| hours.parse::<u64>().ok())
+        .filter(|hours| *hours > 0)
+        .unwrap_or(24);
+    Duration::from_secs(hours * 60 * 60)
+}
+
+/// Returns number of backups that are kept (`BACKUP_RETENTION`, 7 by default).
+///
+fn retention() -> usize {
+    dotenv::var("BACKUP_RETENTION")
+        .ok()
+        .and_then(|retention| retention.parse::<usize>().ok())
+        .filter(|retention| *retention > 0)
+        .unwrap_or(7)
+}
+
+/// Returns current unix timestamp.
+///
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Returns path of database file from `SQLite` database URL.
+///
+fn sqlite_path(url: &str) -> PathBuf {
+    let path: &str = url.trim_start_matches("sqlite:").trim_start_matches("//");
+    PathBuf::from(path.split_once('?').map_or(path, |(path, _)| path))
+}
+
+/// Returns path with given suffix appended to it (e.g. `servers.db` -> `servers.db-wal`).
+///
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name: OsString = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Returns backups that are stored in directory, from oldest to newest.
+///
+fn list(directory: &Path) -> Vec<PathBuf> {
+    let mut backups: Vec<PathBuf> = match fs::read_dir(directory) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .map_or(false, |name| {
+                        name.starts_with(FILE_PREFIX) && name.ends_with(FILE_EXTENSION)
+                    })
+            })
+            .collect::<Vec<PathBuf>>(),
+        Err(_) => Vec::new(),
+    };
+    backups.sort();
+    backups
+}
+
+/// Removes oldest backups so that only `retention` newest ones are kept.
+///
+fn rotate(directory: &Path) {
+    let backups: Vec<PathBuf> = list(directory);
+    let outdated: usize = backups.len().saturating_sub(retention());
+    for backup in &backups[..outdated] {
+        match fs::remove_file(backup) {
+            Ok(()) => logger::log(
+                log::Level::Info,
+                &format!("Removed outdated backup {}", backup.display()),
+            ),
+            Err(error) => logger::log(
+                log::Level::Warn,
+                &format!("Unable to remove backup {}: {}", backup.display(), error),
+            ),
+        }
+    }
+}
+
+/// Makes backup of servers database and removes outdated ones.
+///
+/// Returns path of new backup.
+///
+pub async fn create() -> Result<PathBuf, String> {
+    if SERVERS_BACKEND.get() != Some(&Backend::Sqlite) {
+        return Err(
+            "Backups are supported only for SQLite database, use pg_dump for PostgreSQL"
+                .to_string(),
+        );
+    }
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+
+    let directory: PathBuf = directory();
+    fs::create_dir_all(&directory)
+        .map_err(|error| format!("Unable to create {}: {}", directory.display(), error))?;
+    let path: PathBuf = directory.join(format!("{}{:010}{}", FILE_PREFIX, now(), FILE_EXTENSION));
+    if path.exists() {
+        return Err(format!("Backup {} already exists", path.display()));
+    }
+
+    sqlx::query("VACUUM INTO $1")
+        .bind(path.to_string_lossy().into_owned())
+        .execute(connection)
+        .await
+        .map_err(|error| format!("Unable to make backup: {}", error))?;
+    logger::log(log::Level::Info, &format!("Made backup {}", path.display()));
+    rotate(&directory);
+    Ok(path)
+}
+
+/// Starts loop that periodically makes backups of `SQLite` database. Subsequent calls do nothing.
+///
+pub fn start() {
+    if SERVERS_BACKEND.get() != Some(&Backend::Sqlite) || STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tokio::spawn(async {
+        let mut interval: tokio::time::Interval = tokio::time::interval(interval());
+        let _ = interval.tick().await;
+        loop {
+            let _ = interval.tick().await;
+            if let Err(error) = create().await {
+                logger::log(log::Level::Error, &error);
+            }
+        }
+    });
+}
+
+/// Checks that backup is intact and that its schema is known to this build.
+///
+/// Returns schema version of backup.
+///
+async fn validate(backup: &Path) -> Result<i64, String> {
+    sqlx::any::install_default_drivers();
+    let mut connection: AnyConnection =
+        AnyConnection::connect(&format!("sqlite://{}?mode=ro", backup.display()))
+            .await
+            .map_err(|error| format!("Unable to open {}: {}", backup.display(), error))?;
+
+    let (integrity,): (String,) = sqlx::query_as("PRAGMA integrity_check")
+        .fetch_one(&mut connection)
+        .await
+        .map_err(|error| format!("Unable to check integrity of backup: {}", error))?;
+    if integrity != "ok" {
+        return Err(format!("Backup is corrupted: {}", integrity));
+    }
+    let applied: Vec<(i64, Vec<u8>)> = sqlx::query_as(
+        "SELECT version, checksum FROM _sqlx_migrations WHERE success = 1 ORDER BY version",
+    )
+    .fetch_all(&mut connection)
+    .await
+    .map_err(|error| format!("Unable to read schema version of backup: {}", error))?;
+    let _ = connection.close().await;
+
+    for (version, checksum) in &applied {
+        match SQLITE_MIGRATOR
+            .iter()
+            .find(|migration| migration.version == *version)
+        {
+            Some(migration) if *migration.checksum == checksum[..] => {}
+            Some(_) => {
+                return Err(format!(
+                    "Migration {} of backup differs from the one of this build",
+                    version
+                ))
+            }
+            None => {
+                return Err(format!(
+                    "Schema version {} of backup is unknown to this build",
+                    version
+                ))
+            }
+        }
+    }
+    applied
+        .last()
+        .map(|(version, _)| *version)
+        .ok_or_else(|| "Backup has no applied migrations".to_string())
+}
+
+/// Replaces servers database file with given backup.
+///
+/// Should be called only while bots are not running. Replaced database is kept
+/// next to it with `.pre-restore-<timestamp>` suffix.
+///
+pub async fn restore(backup: &Path) -> Result<(), String> {
+    let url: String = super::servers_database_url();
+    if Backend::from_url(&url) != Some(Backend::Sqlite) {
+        return Err(
+            "Restoring is supported only for SQLite database, use pg_restore for PostgreSQL"
+                .to_string(),
+        );
+    }
+    if !backup.is_file() {
+        return Err(format!("Backup {} does not exist", backup.display()));
+    }
+
+    let version: i64 = validate(backup).await?;
+    let pending: usize = SQLITE_MIGRATOR
+        .iter()
+        .filter(|migration: &&Migration| migration.version > version)
+        .count();
+
+    let database: PathBuf = sqlite_path(&url);
+    let staging: PathBuf = with_suffix(&database, ".restoring");
+    let _ =
+        fs::copy(backup, &staging).map_err(|error| format!("Unable to copy backup: {}", error))?;
+    if database.exists() {
+        let replaced: PathBuf = with_suffix(&database, &format!(".pre-restore-{}", now()));
+        for suffix in ["", "-wal", "-shm"] {
+            let file: PathBuf = with_suffix(&database, suffix);
+            if file.exists() {
+                fs::rename(&file, with_suffix(&replaced, suffix)).map_err(|error| {
+                    format!("Unable to move {} away: {}", file.display(), error)
+                })?;
+            }
+        }
+        logger::log(
+            log::Level::Info,
+            &format!("Previous database was kept as {}", replaced.display()),
+        );
+    }
+    fs::rename(&staging, &database)
+        .map_err(|error| format!("Unable to replace database: {}", error))?;
+
+    logger::log(
+        log::Level::Info,
+        &format!(
+            "Restored {} (schema version {}, {} migrations will be applied on next start)",
+            backup.display(),
+            version,
+            pending
+        ),
+    );
+    Ok(())
+}
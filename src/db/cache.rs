@@ -0,0 +1,213 @@
+//! `cache` module implements in-memory cache of guild settings, so that reading them
+//! (e.g. on every message received by music bots) does not need a database round-trip.
+//!
+//! Cache assumes that this process is the only writer of `settings` table: its writes drop
+//! cached entry through [`invalidate`], but other processes that share database (e.g. second
+//! instance of bot or changes made by hand) are not notified. That is why every entry also
+//! expires after [`TTL`], so that changes made elsewhere become visible within that time.
+//!
+//! Every invalidation also bumps generation of guild, so that settings which were being loaded
+//! while they were changed are not put back into cache.
+//!
+
+use crate::{
+    db::{connections::SERVERS_DB, guilds, models::Setting},
+    logger, metrics,
+};
+use serenity::model::id::GuildId;
+use sqlx::AnyPool;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+/// Interval between two consecutive reports of cache statistics.
+///
+const REPORT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How long cached settings are used before they are loaded from database again.
+///
+pub const TTL: Duration = Duration::from_secs(60);
+
+/// Cached settings of guilds along with the moment they were loaded.
+///
+static SETTINGS: OnceLock<Mutex<HashMap<GuildId, (Instant, Setting)>>> = OnceLock::new();
+
+/// Number of invalidations of every guild, so that loads that overlap with writes can be detected.
+///
+static GENERATIONS: OnceLock<Mutex<HashMap<GuildId, u64>>> = OnceLock::new();
+
+/// Number of times the whole cache was cleared.
+///
+static EPOCH: AtomicU64 = AtomicU64::new(0);
+
+/// Number of lookups that were answered from cache.
+///
+static HITS: AtomicU64 = AtomicU64::new(0);
+
+/// Number of lookups that needed a database query.
+///
+static MISSES: AtomicU64 = AtomicU64::new(0);
+
+/// Whether reporting loop was already started.
+///
+static REPORTING: AtomicBool = AtomicBool::new(false);
+
+/// `CacheStats` struct represents statistics of settings cache.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct CacheStats {
+    /// Number of lookups that were answered from cache.
+    ///
+    pub hits: u64,
+    /// Number of lookups that needed a database query.
+    ///
+    pub misses: u64,
+    /// Number of guilds whose settings are cached.
+    ///
+    pub size: usize,
+}
+impl CacheStats {
+    /// Returns share of lookups that were answered from cache (`0.0` if there were none).
+    ///
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            total => self.hits as f64 / total as f64,
+        }
+    }
+}
+
+/// Returns current generation of guild settings along with epoch of cache.
+///
+fn generation(guild_id: GuildId) -> (u64, u64) {
+    let generation: u64 = GENERATIONS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned")
+        .get(&guild_id)
+        .copied()
+        .unwrap_or_default();
+    (EPOCH.load(Ordering::SeqCst), generation)
+}
+
+/// Loads settings of guild from database and puts them into cache,
+/// unless they were invalidated while being loaded.
+///
+/// Returns `None` if guild is not registered.
+///
+pub async fn load(guild_id: GuildId) -> Option<Setting> {
+    let connection: &AnyPool = SERVERS_DB
+        .get()
+        .expect("Connection should be established at this moment");
+    let _timer: metrics::Timer = metrics::Timer::query("settings.load");
+
+    let loaded_generation: (u64, u64) = generation(guild_id);
+    let setting: Option<Setting> = guilds::settings(connection, guild_id.into())
+        .await
+        .expect("Query should be correct");
+    if let Some(setting) = &setting {
+        let mut settings = SETTINGS
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .expect("Lock should not be poisoned");
+        // Generation is compared under the same lock that invalidation holds.
+        if generation(guild_id) == loaded_generation {
+            let _ = settings.insert(guild_id, (Instant::now(), setting.clone()));
+        }
+    }
+    setting
+}
+
+/// Returns settings of guild, querying database only if they are not cached yet
+/// or were cached longer than [`TTL`] ago.
+///
+/// Returns `None` if guild is not registered.
+///
+pub async fn get(guild_id: GuildId) -> Option<Setting> {
+    if let Some(setting) = SETTINGS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned")
+        .get(&guild_id)
+        .filter(|(loaded_at, _)| loaded_at.elapsed() < TTL)
+        .map(|(_, setting)| setting)
+    {
+        let _ = HITS.fetch_add(1, Ordering::Relaxed);
+        return Some(setting.clone());
+    }
+
+    let _ = MISSES.fetch_add(1, Ordering::Relaxed);
+    load(guild_id).await
+}
+
+/// Drops cached settings of guild. Should be called after every write to `settings` table.
+///
+pub fn invalidate(guild_id: GuildId) {
+    let mut settings = SETTINGS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned");
+    let mut generations = GENERATIONS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned");
+    let generation: &mut u64 = generations.entry(guild_id).or_default();
+    *generation = generation.wrapping_add(1);
+    let _ = settings.remove(&guild_id);
+}
+
+/// Drops cached settings of all guilds.
+///
+pub fn clear() {
+    let mut settings = SETTINGS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned");
+    let _ = EPOCH.fetch_add(1, Ordering::SeqCst);
+    settings.clear();
+}
+
+/// Returns current statistics of cache.
+///
+pub fn stats() -> CacheStats {
+    CacheStats {
+        hits: HITS.load(Ordering::Relaxed),
+        misses: MISSES.load(Ordering::Relaxed),
+        size: SETTINGS
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .expect("Lock should not be poisoned")
+            .len(),
+    }
+}
+
+/// Starts loop that periodically logs cache statistics. Subsequent calls (e.g. on reconnection) do nothing.
+///
+pub fn start_reporting() {
+    if REPORTING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tokio::spawn(async {
+        let mut interval: tokio::time::Interval = tokio::time::interval(REPORT_INTERVAL);
+        let _ = interval.tick().await;
+        loop {
+            let _ = interval.tick().await;
+            let stats: CacheStats = stats();
+            logger::log(
+                log::Level::Info,
+                &format!(
+                    "Settings cache: {} guilds, {} hits, {} misses ({:.1}% hit rate)",
+                    stats.size,
+                    stats.hits,
+                    stats.misses,
+                    stats.hit_rate() * 100.0
+                ),
+            );
+        }
+    });
+}
@@ -0,0 +1,91 @@
+//! `cases` module implements queries of moderation cases.
+//!
+
+use crate::db::models::{Case, CaseAction, DbId, Id};
+use serenity::model::Timestamp;
+use sqlx::{any::AnyQueryResult, AnyPool};
+
+/// Maximal number of attempts to claim number of new case.
+///
+const MAX_OPEN_ATTEMPTS: usize = 5;
+
+/// Stores new case with next number of guild and returns it.
+///
+/// Number of case is taken from the latest case of guild, so cases that are opened at the same
+/// time may claim the same number; insertion is repeated with next number in that case.
+///
+pub async fn open(
+    connection: &AnyPool,
+    guild_id: Id,
+    action: CaseAction,
+    target_id: Id,
+    moderator_id: Id,
+    reason: Option<&str>,
+    expires_at: Option<i64>,
+) -> sqlx::Result<Case> {
+    let mut attempts: usize = 0;
+    loop {
+        attempts += 1;
+        let result: sqlx::Result<Case> = sqlx::query_as::<_, Case>(
+            "
+            INSERT INTO cases VALUES ($1, (SELECT COALESCE(MAX(number), 0) + 1 FROM cases WHERE guild_id = $2), $3, $4, $5, $6, $7, $8)
+            RETURNING *
+        ",
+        )
+        .bind(guild_id.db_id())
+        .bind(guild_id.db_id())
+        .bind(action.to_string())
+        .bind(target_id.db_id())
+        .bind(moderator_id.db_id())
+        .bind(reason)
+        .bind(Timestamp::now().unix_timestamp())
+        .bind(expires_at)
+        .fetch_one(connection)
+        .await;
+        match result {
+            Err(sqlx::Error::Database(error))
+                if error.is_unique_violation() && attempts < MAX_OPEN_ATTEMPTS => {}
+            result => return result,
+        }
+    }
+}
+
+/// Returns case of guild with given number.
+///
+pub async fn get(connection: &AnyPool, guild_id: Id, number: u64) -> sqlx::Result<Option<Case>> {
+    sqlx::query_as::<_, Case>("SELECT * FROM cases WHERE guild_id = $1 AND number = $2")
+        .bind(guild_id.db_id())
+        .bind(number as i64)
+        .fetch_optional(connection)
+        .await
+}
+
+/// Returns cases of guild that were opened against user, ordered by number.
+///
+pub async fn list(connection: &AnyPool, guild_id: Id, target_id: Id) -> sqlx::Result<Vec<Case>> {
+    sqlx::query_as::<_, Case>(
+        "SELECT * FROM cases WHERE guild_id = $1 AND target_id = $2 AND action <> 'purge' ORDER BY number",
+    )
+    .bind(guild_id.db_id())
+    .bind(target_id.db_id())
+    .fetch_all(connection)
+    .await
+}
+
+/// Updates reason of case, returning whether such case exists.
+///
+pub async fn set_reason(
+    connection: &AnyPool,
+    guild_id: Id,
+    number: u64,
+    reason: &str,
+) -> sqlx::Result<bool> {
+    let result: AnyQueryResult =
+        sqlx::query("UPDATE cases SET reason = $1 WHERE guild_id = $2 AND number = $3")
+            .bind(reason)
+            .bind(guild_id.db_id())
+            .bind(number as i64)
+            .execute(connection)
+            .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -1,24 +1,69 @@
 //! `db` module provides `connect` function and several global static connection handlers for `Warden` bot.
 //!
+//! Database backend (`SQLite` or `PostgreSQL`) is chosen by scheme of database URL,
+//! so queries are written in SQL subset that both of them understand
+//! (numbered `$N` placeholders, `RETURNING`, `ON CONFLICT`).
+//!
 
 use crate::logger::log;
-use sqlx::{migrate::MigrateDatabase, Sqlite, SqlitePool};
+use sqlx::{migrate::MigrateDatabase, Any, AnyPool};
 use std::sync::OnceLock;
 
-/// Returns new connection pool to `sqlite` database.
+/// `Backend` enum represents database engine that pool is connected to.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// `SQLite` database file, suitable for small single-instance deployments.
+    ///
+    Sqlite,
+    /// `PostgreSQL` server, suitable for deployments with several instances.
+    ///
+    Postgres,
+}
+impl Backend {
+    /// Returns backend that serves given database URL.
+    ///
+    pub fn from_url(url: &str) -> Option<Backend> {
+        match url.split_once(':').map(|(scheme, _)| scheme) {
+            Some("sqlite") => Some(Backend::Sqlite),
+            Some("postgres" | "postgresql") => Some(Backend::Postgres),
+            _ => None,
+        }
+    }
+
+    /// Returns name of backend.
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Backend::Sqlite => "sqlite",
+            Backend::Postgres => "postgres",
+        }
+    }
+}
+
+/// Returns new connection pool to database with given URL (database is created if it does not exist).
 ///
-pub async fn connect(name: &str) -> SqlitePool {
-    let db_url: &str = &format!("sqlite://{}", name);
+pub async fn connect(url: &str) -> AnyPool {
+    let backend: Backend =
+        Backend::from_url(url).expect("Database URL should be either sqlite or postgres one");
+    sqlx::any::install_default_drivers();
 
-    if !Sqlite::database_exists(db_url).await.unwrap_or(false) {
-        Sqlite::create_database(db_url)
+    if !Any::database_exists(url).await.unwrap_or(false) {
+        Any::create_database(url)
             .await
             .expect("Creation of database should be possible");
-        log(log::Level::Info, &format!("Created database {}", db_url));
+        log(
+            log::Level::Info,
+            &format!("Created {} database", backend.as_str()),
+        );
     }
-    SqlitePool::connect(db_url).await.unwrap()
+    AnyPool::connect(url).await.unwrap()
 }
 
+/// Backend of `SERVERS_DB`.
+///
+pub static SERVERS_BACKEND: OnceLock<Backend> = OnceLock::new();
+
 /// Global `servers.db` pool.
 ///
-pub static SERVERS_DB: OnceLock<SqlitePool> = OnceLock::new();
+pub static SERVERS_DB: OnceLock<AnyPool> = OnceLock::new();
@@ -0,0 +1,48 @@
+//! `guilds` module implements queries of registered guilds and their settings.
+//!
+
+use crate::db::models::{DbId, Id, Setting};
+use sqlx::{Any, AnyPool, Transaction};
+
+/// Returns whether guild is registered.
+///
+pub async fn is_registered(connection: &AnyPool, guild_id: Id) -> sqlx::Result<bool> {
+    let guild: Option<(i64,)> =
+        sqlx::query_as("SELECT discord_id FROM guilds WHERE discord_id = $1")
+            .bind(guild_id.db_id())
+            .fetch_optional(connection)
+            .await?;
+    Ok(guild.is_some())
+}
+
+/// Registers guild with default settings and free music bots.
+///
+pub async fn register(connection: &AnyPool, guild_id: Id) -> sqlx::Result<()> {
+    let mut transaction: Transaction<'_, Any> = connection.begin().await?;
+    let (settings_id,): (i64,) = sqlx::query_as("INSERT INTO settings DEFAULT VALUES RETURNING id")
+        .fetch_one(&mut *transaction)
+        .await?;
+    sqlx::query("INSERT INTO guilds VALUES ($1, $2)")
+        .bind(guild_id.db_id())
+        .bind(settings_id)
+        .execute(&mut *transaction)
+        .await?;
+    sqlx::query(
+        "INSERT INTO music_bots VALUES ($1, 'music1 ', NULL), ($1, 'music2 ', NULL), ($1, 'music3 ', NULL)",
+    )
+    .bind(guild_id.db_id())
+    .execute(&mut *transaction)
+    .await?;
+    transaction.commit().await
+}
+
+/// Returns settings of guild, or `None` if guild is not registered.
+///
+pub async fn settings(connection: &AnyPool, guild_id: Id) -> sqlx::Result<Option<Setting>> {
+    sqlx::query_as::<_, Setting>(
+        "SELECT * FROM settings WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = $1)",
+    )
+    .bind(guild_id.db_id())
+    .fetch_optional(connection)
+    .await
+}
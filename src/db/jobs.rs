@@ -0,0 +1,69 @@
+//! `jobs` module implements queries of persistent jobs that scheduler executes.
+//!
+
+use crate::db::models::Job;
+use sqlx::AnyPool;
+
+/// Stores job of given kind that should be executed at given unix timestamp.
+///
+pub async fn schedule(
+    connection: &AnyPool,
+    kind: &str,
+    payload: &str,
+    due_at: i64,
+) -> sqlx::Result<()> {
+    sqlx::query("INSERT INTO jobs (due_at, kind, payload) VALUES ($1, $2, $3)")
+        .bind(due_at)
+        .bind(kind)
+        .bind(payload)
+        .execute(connection)
+        .await?;
+    Ok(())
+}
+
+/// Removes every job of given kind with given payload.
+///
+pub async fn cancel(connection: &AnyPool, kind: &str, payload: &str) -> sqlx::Result<()> {
+    sqlx::query("DELETE FROM jobs WHERE kind = $1 AND payload = $2")
+        .bind(kind)
+        .bind(payload)
+        .execute(connection)
+        .await?;
+    Ok(())
+}
+
+/// Returns jobs that are due at given unix timestamp, earliest first.
+///
+pub async fn due(connection: &AnyPool, now: i64) -> sqlx::Result<Vec<Job>> {
+    sqlx::query_as::<_, Job>("SELECT * FROM jobs WHERE due_at <= $1 ORDER BY due_at")
+        .bind(now)
+        .fetch_all(connection)
+        .await
+}
+
+/// Postpones job to given unix timestamp, recording number of failed attempts.
+///
+pub async fn retry(
+    connection: &AnyPool,
+    job: &Job,
+    attempts: i64,
+    due_at: i64,
+) -> sqlx::Result<()> {
+    sqlx::query("UPDATE jobs SET due_at = $1, attempts = $2 WHERE id = $3")
+        .bind(due_at)
+        .bind(attempts)
+        .bind(job.id as i64)
+        .execute(connection)
+        .await?;
+    Ok(())
+}
+
+/// Removes job that was executed or dropped.
+///
+pub async fn complete(connection: &AnyPool, job: &Job) -> sqlx::Result<()> {
+    sqlx::query("DELETE FROM jobs WHERE id = $1")
+        .bind(job.id as i64)
+        .execute(connection)
+        .await?;
+    Ok(())
+}
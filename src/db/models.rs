@@ -20,8 +20,15 @@ impl ToString for Id {
 impl FromStr for Id {
     type Err = ParseIntError;
 
+    /// Parses either raw id or channel, role or user mention.
+    ///
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Id(u64::from_str(s)?))
+        let id: &str = s
+            .strip_prefix('<')
+            .and_then(|s| s.strip_suffix('>'))
+            .map(|s| s.trim_start_matches(|c: char| matches!(c, '#' | '@' | '&' | '!')))
+            .unwrap_or(s);
+        Ok(Id(u64::from_str(id)?))
     }
 }
 impl TryFrom<i64> for Id {
@@ -31,6 +38,11 @@ impl TryFrom<i64> for Id {
         Ok(Id(u64::try_from(value)?))
     }
 }
+impl From<u64> for Id {
+    fn from(value: u64) -> Self {
+        Id(value)
+    }
+}
 impl From<ChannelId> for Id {
     fn from(value: ChannelId) -> Self {
         Id(value.0)
@@ -71,6 +83,11 @@ impl From<Id> for UserId {
         UserId(value.0)
     }
 }
+impl From<Id> for i64 {
+    fn from(value: Id) -> Self {
+        value.0 as i64
+    }
+}
 
 /// `ForeignId` newtype encapsulates discord API id's that are represented by `u64`,
 /// but stored in database as `Option<i64>` due to possible nullability.
@@ -88,6 +105,31 @@ impl TryFrom<Option<i64>> for ForeignId {
     }
 }
 
+/// `Flag` newtype encapsulates boolean values that are stored in database as `0` or `1` integers.
+///
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Flag(pub bool);
+impl TryFrom<i64> for Flag {
+    type Error = TryFromIntError;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        Ok(Flag(value != 0))
+    }
+}
+
+/// `DbId` trait converts discord API id's into `i64` that they are stored as in database.
+///
+pub trait DbId {
+    /// Returns id as it is stored in database.
+    ///
+    fn db_id(&self) -> i64;
+}
+impl<T: Copy + Into<Id>> DbId for T {
+    fn db_id(&self) -> i64 {
+        i64::from(Into::<Id>::into(*self))
+    }
+}
+
 #[derive(Debug, FromRow)]
 pub struct Channel {
     #[sqlx(try_from = "i64", default)]
@@ -115,7 +157,7 @@ pub struct Guild {
     pub settings_id: u64,
 }
 
-#[derive(Debug, FromRow)]
+#[derive(Clone, Debug, FromRow)]
 pub struct Setting {
     #[sqlx(try_from = "i64", default)]
     pub id: u64,
@@ -134,6 +176,15 @@ pub struct Setting {
 
     #[sqlx(try_from = "Option<i64>", default)]
     pub member_role_id: ForeignId,
+
+    #[sqlx(default)]
+    pub disabled_log_events: i64,
+
+    #[sqlx(default)]
+    pub prefix: Option<String>,
+
+    #[sqlx(default)]
+    pub language: Option<String>,
 }
 
 #[derive(Debug, FromRow)]
@@ -148,6 +199,21 @@ pub struct MusicBot {
     pub on_channel_id: ForeignId,
 }
 
+#[derive(Debug, FromRow)]
+pub struct QueuedTrack {
+    #[sqlx(try_from = "i64", default)]
+    pub guild_id: Id,
+
+    #[sqlx(default)]
+    pub prefix: String,
+
+    #[sqlx(default)]
+    pub position: i64,
+
+    #[sqlx(default)]
+    pub url: String,
+}
+
 #[derive(Debug, FromRow)]
 pub struct UnregisteredMember {
     #[sqlx(try_from = "i64", default)]
@@ -155,4 +221,440 @@ pub struct UnregisteredMember {
 
     #[sqlx(try_from = "i64", default)]
     pub guild_id: Id,
+
+    #[sqlx(try_from = "i64", default)]
+    pub is_paused: Flag,
+
+    #[sqlx(try_from = "i64", default)]
+    pub is_quarantined: Flag,
+}
+
+#[derive(Debug, FromRow)]
+pub struct RaidSetting {
+    #[sqlx(try_from = "i64", default)]
+    pub guild_id: Id,
+
+    #[sqlx(default)]
+    pub join_threshold: Option<i64>,
+
+    #[sqlx(default)]
+    pub join_period: Option<i64>,
+
+    #[sqlx(default)]
+    pub min_account_age: Option<i64>,
+
+    #[sqlx(try_from = "Option<i64>", default)]
+    pub quarantine_role_id: ForeignId,
+
+    #[sqlx(try_from = "i64", default)]
+    pub lockdown: Flag,
+}
+
+/// `CaseAction` enum represents moderation action that was taken in a case.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CaseAction {
+    #[default]
+    Warn,
+    Timeout,
+    Kick,
+    Ban,
+    Unban,
+    Purge,
+    Role,
+    RemoveRole,
+}
+impl CaseAction {
+    /// Returns name of action as it is stored in database.
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CaseAction::Warn => "warn",
+            CaseAction::Timeout => "timeout",
+            CaseAction::Kick => "kick",
+            CaseAction::Ban => "ban",
+            CaseAction::Unban => "unban",
+            CaseAction::Purge => "purge",
+            CaseAction::Role => "role",
+            CaseAction::RemoveRole => "remove_role",
+        }
+    }
+}
+impl ToString for CaseAction {
+    fn to_string(&self) -> String {
+        self.as_str().to_string()
+    }
+}
+impl FromStr for CaseAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "warn" => CaseAction::Warn,
+            "timeout" => CaseAction::Timeout,
+            "kick" => CaseAction::Kick,
+            "ban" => CaseAction::Ban,
+            "unban" => CaseAction::Unban,
+            "purge" => CaseAction::Purge,
+            "role" => CaseAction::Role,
+            "remove_role" => CaseAction::RemoveRole,
+            _ => return Err(format!("Unknown case action '{}'", s)),
+        })
+    }
+}
+impl TryFrom<String> for CaseAction {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        CaseAction::from_str(&value)
+    }
+}
+
+/// `Case` struct represents moderation action that was recorded in `cases` table.
+///
+#[derive(Debug, FromRow)]
+pub struct Case {
+    /// Guild where action was taken.
+    ///
+    #[sqlx(try_from = "i64", default)]
+    pub guild_id: Id,
+
+    /// Number of case, which is unique within guild and starts from 1.
+    ///
+    #[sqlx(try_from = "i64", default)]
+    pub number: u64,
+
+    /// Action that was taken.
+    ///
+    #[sqlx(try_from = "String", default)]
+    pub action: CaseAction,
+
+    /// User that action was taken against (or channel for purges).
+    ///
+    #[sqlx(try_from = "i64", default)]
+    pub target_id: Id,
+
+    /// User that took action (bot itself for automatic actions).
+    ///
+    #[sqlx(try_from = "i64", default)]
+    pub moderator_id: Id,
+
+    /// Reason of action, if moderator gave one.
+    ///
+    #[sqlx(default)]
+    pub reason: Option<String>,
+
+    /// Unix timestamp of moment when case was opened.
+    ///
+    #[sqlx(default)]
+    pub created_at: i64,
+
+    /// Unix timestamp of moment when temporary action ends.
+    ///
+    #[sqlx(default)]
+    pub expires_at: Option<i64>,
+}
+
+/// `Job` struct represents delayed task that is stored in `jobs` table until it is executed.
+///
+#[derive(Debug, FromRow)]
+pub struct Job {
+    /// Identifier of job.
+    ///
+    #[sqlx(try_from = "i64", default)]
+    pub id: u64,
+
+    /// Unix timestamp of moment when job should be executed (or retried).
+    ///
+    #[sqlx(default)]
+    pub due_at: i64,
+
+    /// Kind of task, e.g. `unban`.
+    ///
+    #[sqlx(default)]
+    pub kind: String,
+
+    /// Space-separated ids that task is executed with.
+    ///
+    #[sqlx(default)]
+    pub payload: String,
+
+    /// Number of failed attempts to execute job.
+    ///
+    #[sqlx(default)]
+    pub attempts: i64,
+}
+
+/// `AutomodRuleKind` enum represents kind of violation that automod rule detects.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum AutomodRuleKind {
+    /// Too many messages in a time window.
+    ///
+    #[default]
+    Rate,
+    /// Too many identical messages in a time window.
+    ///
+    Duplicates,
+    /// Too many mentions in one message.
+    ///
+    Mentions,
+    /// Discord invite links.
+    ///
+    Invites,
+    /// Messages that match regular expression.
+    ///
+    Words,
+}
+impl AutomodRuleKind {
+    /// Returns name of rule kind as it is stored in database.
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AutomodRuleKind::Rate => "rate",
+            AutomodRuleKind::Duplicates => "duplicates",
+            AutomodRuleKind::Mentions => "mentions",
+            AutomodRuleKind::Invites => "invites",
+            AutomodRuleKind::Words => "words",
+        }
+    }
+}
+impl ToString for AutomodRuleKind {
+    fn to_string(&self) -> String {
+        self.as_str().to_string()
+    }
+}
+impl FromStr for AutomodRuleKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "rate" => AutomodRuleKind::Rate,
+            "duplicates" => AutomodRuleKind::Duplicates,
+            "mentions" => AutomodRuleKind::Mentions,
+            "invites" => AutomodRuleKind::Invites,
+            "words" => AutomodRuleKind::Words,
+            _ => return Err(format!("Unknown automod rule '{}'", s)),
+        })
+    }
+}
+impl TryFrom<String> for AutomodRuleKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        AutomodRuleKind::from_str(&value)
+    }
+}
+
+/// `AutomodAction` enum represents action that is taken when automod rule is violated.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum AutomodAction {
+    /// Deletes message.
+    ///
+    #[default]
+    Delete,
+    /// Deletes message and warns author.
+    ///
+    Warn,
+    /// Deletes message and times out author.
+    ///
+    Timeout,
+}
+impl AutomodAction {
+    /// Returns name of action as it is stored in database.
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AutomodAction::Delete => "delete",
+            AutomodAction::Warn => "warn",
+            AutomodAction::Timeout => "timeout",
+        }
+    }
+}
+impl ToString for AutomodAction {
+    fn to_string(&self) -> String {
+        self.as_str().to_string()
+    }
+}
+impl FromStr for AutomodAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "delete" => AutomodAction::Delete,
+            "warn" => AutomodAction::Warn,
+            "timeout" => AutomodAction::Timeout,
+            _ => return Err(format!("Unknown automod action '{}'", s)),
+        })
+    }
+}
+impl TryFrom<String> for AutomodAction {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        AutomodAction::from_str(&value)
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct AutomodRule {
+    #[sqlx(try_from = "i64", default)]
+    pub guild_id: Id,
+
+    #[sqlx(try_from = "String", default)]
+    pub kind: AutomodRuleKind,
+
+    #[sqlx(default)]
+    pub threshold: Option<i64>,
+
+    #[sqlx(default)]
+    pub period: Option<i64>,
+
+    #[sqlx(default)]
+    pub pattern: Option<String>,
+
+    #[sqlx(try_from = "String", default)]
+    pub action: AutomodAction,
+}
+
+/// `LogCategory` enum represents category of events that are logged in discord channel.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LogCategory {
+    #[default]
+    Registration,
+    Moderation,
+    Music,
+    Messages,
+    Voice,
+    ServerChanges,
+    Errors,
+}
+impl LogCategory {
+    /// All log categories.
+    ///
+    pub const ALL: [LogCategory; 7] = [
+        LogCategory::Registration,
+        LogCategory::Moderation,
+        LogCategory::Music,
+        LogCategory::Messages,
+        LogCategory::Voice,
+        LogCategory::ServerChanges,
+        LogCategory::Errors,
+    ];
+
+    /// Returns name of category as it is stored in database.
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogCategory::Registration => "registration",
+            LogCategory::Moderation => "moderation",
+            LogCategory::Music => "music",
+            LogCategory::Messages => "messages",
+            LogCategory::Voice => "voice",
+            LogCategory::ServerChanges => "server_changes",
+            LogCategory::Errors => "errors",
+        }
+    }
+}
+impl ToString for LogCategory {
+    fn to_string(&self) -> String {
+        self.as_str().to_string()
+    }
+}
+impl FromStr for LogCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LogCategory::ALL
+            .into_iter()
+            .find(|category| category.as_str() == s)
+            .ok_or_else(|| format!("Unknown log category '{}'", s))
+    }
+}
+impl TryFrom<String> for LogCategory {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        LogCategory::from_str(&value)
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct LogRoute {
+    #[sqlx(try_from = "i64", default)]
+    pub guild_id: Id,
+
+    #[sqlx(try_from = "String", default)]
+    pub category: LogCategory,
+
+    #[sqlx(try_from = "i64", default)]
+    pub channel_id: Id,
+}
+
+#[derive(Debug, FromRow)]
+pub struct CommandAlias {
+    #[sqlx(try_from = "i64", default)]
+    pub guild_id: Id,
+
+    #[sqlx(default)]
+    pub alias: String,
+
+    #[sqlx(default)]
+    pub command: String,
+}
+
+/// `Language` enum represents language that bot replies are translated into.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Language {
+    #[default]
+    En,
+    Ru,
+}
+impl Language {
+    /// All supported languages.
+    ///
+    pub const ALL: [Language; 2] = [Language::En, Language::Ru];
+
+    /// Returns code of language as it is stored in database.
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::En => "en",
+            Language::Ru => "ru",
+        }
+    }
+}
+impl ToString for Language {
+    fn to_string(&self) -> String {
+        self.as_str().to_string()
+    }
+}
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Language::ALL
+            .into_iter()
+            .find(|language| language.as_str() == s)
+            .ok_or_else(|| format!("Unknown language '{}'", s))
+    }
+}
+impl TryFrom<String> for Language {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Language::from_str(&value)
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct UserLanguage {
+    #[sqlx(try_from = "i64", default)]
+    pub user_id: Id,
+
+    #[sqlx(try_from = "String", default)]
+    pub language: Language,
 }
@@ -0,0 +1,67 @@
+//! `music_bots` module implements queries of music bots and voice channels they are in.
+//!
+
+use crate::db::models::{DbId, Id, MusicBot};
+use sqlx::{Any, AnyPool, Transaction};
+
+/// Returns music bot of guild that is in given voice channel.
+///
+pub async fn in_channel(
+    connection: &AnyPool,
+    guild_id: Id,
+    channel_id: Id,
+) -> sqlx::Result<Option<MusicBot>> {
+    sqlx::query_as::<_, MusicBot>(
+        "SELECT * FROM music_bots WHERE guild_id = $1 AND on_channel_id = $2",
+    )
+    .bind(guild_id.db_id())
+    .bind(channel_id.db_id())
+    .fetch_optional(connection)
+    .await
+}
+
+/// Returns music bot of guild that is not in any voice channel.
+///
+pub async fn free(connection: &AnyPool, guild_id: Id) -> sqlx::Result<Option<MusicBot>> {
+    sqlx::query_as::<_, MusicBot>(
+        "SELECT * FROM music_bots WHERE guild_id = $1 AND on_channel_id IS NULL",
+    )
+    .bind(guild_id.db_id())
+    .fetch_optional(connection)
+    .await
+}
+
+/// Records that music bot with given prefix joined voice channel of guild.
+///
+pub async fn join(
+    connection: &AnyPool,
+    guild_id: Id,
+    channel_id: Id,
+    prefix: &str,
+) -> sqlx::Result<()> {
+    let mut transaction: Transaction<'_, Any> = connection.begin().await?;
+    sqlx::query("INSERT INTO channels VALUES ($1, $2) ON CONFLICT DO NOTHING")
+        .bind(channel_id.db_id())
+        .bind(guild_id.db_id())
+        .execute(&mut *transaction)
+        .await?;
+    sqlx::query("UPDATE music_bots SET on_channel_id = $1 WHERE guild_id = $2 AND prefix = $3")
+        .bind(channel_id.db_id())
+        .bind(guild_id.db_id())
+        .bind(prefix)
+        .execute(&mut *transaction)
+        .await?;
+    transaction.commit().await
+}
+
+/// Forgets voice channel, which frees music bot that was in it.
+///
+/// Settings that were referencing channel are reset by foreign key as well.
+///
+pub async fn leave(connection: &AnyPool, channel_id: Id) -> sqlx::Result<()> {
+    sqlx::query("DELETE FROM channels WHERE discord_id = $1")
+        .bind(channel_id.db_id())
+        .execute(connection)
+        .await?;
+    Ok(())
+}
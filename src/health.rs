@@ -0,0 +1,430 @@
+//! `health` module implements optional HTTP server that exposes health and status of bots,
+//! so that they can be monitored by orchestrators and dashboards.
+//!
+//! Server is started only if `HEALTH_ADDRESS` variable is set (e.g. `127.0.0.1:8080`)
+//! and answers following `GET` requests:
+//! - `/healthz` - process is alive;
+//! - `/readyz` - all shards of every bot are connected (received `ready`) and database is reachable;
+//! - `/status` - JSON with shard latencies, guild counts and voice connections of every bot;
+//! - `/metrics` - metrics in Prometheus text format (see `metrics` module).
+//!
+//! Locally it can be checked with `curl -i http://127.0.0.1:8080/status`.
+//!
+
+use crate::{
+    bot::{
+        supervisor::{self, BotState, BotStatus},
+        BotHandle,
+    },
+    db::{
+        cache::{self, CacheStats},
+        connections::{SERVERS_BACKEND, SERVERS_DB},
+    },
+    logger, metrics,
+};
+use serde_json::{json, Value};
+use serenity::{
+    cache::Cache,
+    client::bridge::gateway::{ShardId, ShardManager, ShardRunnerInfo},
+    gateway::ConnectionStage,
+    model::id::GuildId,
+    prelude::Mutex,
+};
+use songbird::{Call, Songbird};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::MutexGuard,
+};
+
+/// Maximal size of request head that is read.
+///
+const MAX_REQUEST_SIZE: usize = 8192;
+
+/// Time that client is given to send request.
+///
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time that database is given to answer readiness probe.
+///
+const DATABASE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// `Response` struct represents HTTP response of server.
+///
+struct Response {
+    /// Status line code and reason.
+    ///
+    status: (u16, &'static str),
+    /// Value of `Content-Type` header.
+    ///
+    content_type: &'static str,
+    /// Body of response.
+    ///
+    body: String,
+}
+impl Response {
+    /// Creates plain text response.
+    ///
+    fn text(status: (u16, &'static str), body: &str) -> Response {
+        Response {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: format!("{}\n", body),
+        }
+    }
+
+    /// Creates JSON response.
+    ///
+    fn json(status: (u16, &'static str), body: &Value) -> Response {
+        Response {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    /// Serializes response, omitting body for `HEAD` requests.
+    ///
+    fn to_bytes(&self, with_body: bool) -> Vec<u8> {
+        let mut bytes: Vec<u8> = format!(
+            concat!(
+                "HTTP/1.1 {} {}\r\n",
+                "Content-Type: {}\r\n",
+                "Content-Length: {}\r\n",
+                "Cache-Control: no-store\r\n",
+                "Connection: close\r\n\r\n"
+            ),
+            self.status.0,
+            self.status.1,
+            self.content_type,
+            self.body.len()
+        )
+        .into_bytes();
+        if with_body {
+            bytes.extend_from_slice(self.body.as_bytes());
+        }
+        bytes
+    }
+}
+
+/// Starts HTTP server if `HEALTH_ADDRESS` is set.
+///
+pub async fn start(bots: Vec<BotHandle>) {
+    let address: String = match dotenv::var("HEALTH_ADDRESS") {
+        Ok(address) if !address.trim().is_empty() => address.trim().to_string(),
+        _ => return,
+    };
+    let listener: TcpListener = match TcpListener::bind(&address).await {
+        Ok(listener) => listener,
+        Err(error) => {
+            logger::log(
+                log::Level::Error,
+                &format!("Unable to start health server on {}: {}", address, error),
+            );
+            return;
+        }
+    };
+    logger::log(
+        log::Level::Info,
+        &format!("Health server is listening on {}", address),
+    );
+
+    let bots: Arc<Vec<BotHandle>> = Arc::new(bots);
+    let _ = tokio::spawn(async move {
+        loop {
+            let (stream, _) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(error) => {
+                    logger::log(
+                        log::Level::Warn,
+                        &format!("Health server was unable to accept connection: {}", error),
+                    );
+                    continue;
+                }
+            };
+            let bots: Arc<Vec<BotHandle>> = Arc::clone(&bots);
+            let _ = tokio::spawn(async move {
+                if let Err(error) = serve(stream, &bots).await {
+                    logger::log(
+                        log::Level::Debug,
+                        &format!("Health server was unable to answer request: {}", error),
+                    );
+                }
+            });
+        }
+    });
+}
+
+/// Reads request head from connection.
+///
+/// Returns `None` if request was too large or client did not send it in time.
+///
+async fn read_request(stream: &mut TcpStream) -> std::io::Result<Option<String>> {
+    let mut request: Vec<u8> = Vec::new();
+    let mut buffer: [u8; 1024] = [0; 1024];
+    let read = async {
+        while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+            let read: usize = stream.read(&mut buffer).await?;
+            if read == 0 || request.len() + read > MAX_REQUEST_SIZE {
+                return Ok(false);
+            }
+            request.extend_from_slice(&buffer[..read]);
+        }
+        Ok(true)
+    };
+    match tokio::time::timeout(REQUEST_TIMEOUT, read).await {
+        Ok(Ok(true)) => Ok(Some(String::from_utf8_lossy(&request).into_owned())),
+        Ok(Ok(false)) | Err(_) => Ok(None),
+        Ok(Err(error)) => Err(error),
+    }
+}
+
+/// Answers single request and closes connection.
+///
+async fn serve(mut stream: TcpStream, bots: &[BotHandle]) -> std::io::Result<()> {
+    let request: String = match read_request(&mut stream).await? {
+        Some(request) => request,
+        None => {
+            let response: Response = Response::text((400, "Bad Request"), "bad request");
+            return stream.write_all(&response.to_bytes(true)).await;
+        }
+    };
+    let mut request_line: std::str::SplitWhitespace<'_> = request
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split_whitespace();
+    let method: &str = request_line.next().unwrap_or_default();
+    let target: &str = request_line.next().unwrap_or_default();
+    let path: &str = target.split('?').next().unwrap_or_default();
+
+    let response: Response = if method != "GET" && method != "HEAD" {
+        Response::text((405, "Method Not Allowed"), "method not allowed")
+    } else {
+        route(path, bots).await
+    };
+    stream
+        .write_all(&response.to_bytes(method != "HEAD"))
+        .await?;
+    stream.shutdown().await
+}
+
+/// Builds response for given path.
+///
+async fn route(path: &str, bots: &[BotHandle]) -> Response {
+    match path {
+        "/healthz" => Response::text((200, "OK"), "ok"),
+        "/readyz" => {
+            let mut problems: Vec<String> = Vec::new();
+            for bot in bots {
+                if !is_ready(bot).await {
+                    problems.push(format!("{} is not ready", bot.name));
+                }
+            }
+            if !database_reachable().await {
+                problems.push("database is not reachable".to_string());
+            }
+            if problems.is_empty() {
+                Response::text((200, "OK"), "ready")
+            } else {
+                Response::text((503, "Service Unavailable"), &problems.join("\n"))
+            }
+        }
+        "/status" => Response::json((200, "OK"), &status(bots).await),
+        "/metrics" => Response {
+            status: (200, "OK"),
+            content_type: "text/plain; version=0.0.4; charset=utf-8",
+            body: metrics(bots).await,
+        },
+        _ => Response::text((404, "Not Found"), "not found"),
+    }
+}
+
+/// `ShardInfo` struct represents state of shard of running client.
+///
+struct ShardInfo {
+    /// Id of shard.
+    ///
+    id: u64,
+    /// Stage of connection to gateway.
+    ///
+    stage: ConnectionStage,
+    /// Latency of last heartbeat, if any was acknowledged.
+    ///
+    latency: Option<Duration>,
+}
+
+/// Returns shards of currently running client of bot, sorted by their ids.
+///
+async fn shards(bot: &BotHandle) -> Vec<ShardInfo> {
+    let shard_manager: Option<Arc<Mutex<ShardManager>>> = bot
+        .shard_manager
+        .lock()
+        .expect("Lock should not be poisoned")
+        .clone();
+    let shard_manager: Arc<Mutex<ShardManager>> = match shard_manager {
+        Some(shard_manager) => shard_manager,
+        None => return Vec::new(),
+    };
+    let runners: Arc<Mutex<HashMap<ShardId, ShardRunnerInfo>>> =
+        Arc::clone(&shard_manager.lock().await.runners);
+    let mut shards: Vec<ShardInfo> = runners
+        .lock()
+        .await
+        .iter()
+        .map(|(id, runner)| ShardInfo {
+            id: id.0,
+            stage: runner.stage,
+            latency: runner.latency,
+        })
+        .collect::<Vec<ShardInfo>>();
+    shards.sort_by_key(|shard| shard.id);
+    shards
+}
+
+/// Returns whether bot is running and all of its shards are connected.
+///
+async fn is_ready(bot: &BotHandle) -> bool {
+    let running: bool = supervisor::statuses()
+        .iter()
+        .any(|(name, status)| *name == bot.name && status.state == BotState::Running);
+    let shards: Vec<ShardInfo> = shards(bot).await;
+    running
+        && !shards.is_empty()
+        && shards
+            .iter()
+            .all(|shard| shard.stage == ConnectionStage::Connected)
+}
+
+/// Returns whether database answers simple query in time.
+///
+async fn database_reachable() -> bool {
+    match SERVERS_DB.get() {
+        Some(connection) => matches!(
+            tokio::time::timeout(
+                DATABASE_TIMEOUT,
+                sqlx::query("SELECT 1").execute(connection)
+            )
+            .await,
+            Ok(Ok(_))
+        ),
+        None => false,
+    }
+}
+
+/// Returns guilds that bot is in according to cache of its running client.
+///
+fn guilds(bot: &BotHandle) -> Vec<GuildId> {
+    let cache: Option<Arc<Cache>> = bot
+        .cache
+        .lock()
+        .expect("Lock should not be poisoned")
+        .clone();
+    cache.map(|cache| cache.guilds()).unwrap_or_default()
+}
+
+/// Returns number of guilds where music bot is connected to voice channel
+/// and total number of tracks in its queues.
+///
+async fn voice(songbird: &Songbird, guilds: &[GuildId]) -> (usize, usize) {
+    let mut connections: usize = 0;
+    let mut tracks: usize = 0;
+    for guild_id in guilds {
+        if let Some(call) = songbird.get(*guild_id) {
+            let call: MutexGuard<'_, Call> = call.lock().await;
+            if call.current_connection().is_some() {
+                connections += 1;
+            }
+            tracks += call.queue().len();
+        }
+    }
+    (connections, tracks)
+}
+
+/// Renders collected metrics together with voice connections and queue lengths of music bots.
+///
+async fn metrics(bots: &[BotHandle]) -> String {
+    let mut output: String = metrics::render();
+    let mut connections: Vec<(String, f64)> = Vec::new();
+    let mut tracks: Vec<(String, f64)> = Vec::new();
+    for bot in bots {
+        if let Some(songbird) = &bot.songbird {
+            let (bot_connections, bot_tracks): (usize, usize) = voice(songbird, &guilds(bot)).await;
+            connections.push((bot.name.to_string(), bot_connections as f64));
+            tracks.push((bot.name.to_string(), bot_tracks as f64));
+        }
+    }
+    metrics::render_gauge(
+        &mut output,
+        "warden_voice_connections",
+        "Number of voice channels that music bot is connected to.",
+        "bot",
+        &connections,
+    );
+    metrics::render_gauge(
+        &mut output,
+        "warden_music_queue_tracks",
+        "Number of tracks in queues of music bot.",
+        "bot",
+        &tracks,
+    );
+    output
+}
+
+/// Collects status of bots, database and settings cache.
+///
+async fn status(bots: &[BotHandle]) -> Value {
+    let statuses: Vec<(&'static str, BotStatus)> = supervisor::statuses();
+    let mut bots_status: Vec<Value> = Vec::new();
+    for bot in bots {
+        let status: Option<BotStatus> = statuses
+            .iter()
+            .find(|(name, _)| *name == bot.name)
+            .map(|(_, status)| *status);
+
+        let shards: Vec<Value> = shards(bot)
+            .await
+            .iter()
+            .map(|shard| {
+                json!({
+                    "id": shard.id,
+                    "stage": format!("{:?}", shard.stage),
+                    "latency_ms": shard.latency.map(|latency| latency.as_millis() as u64),
+                })
+            })
+            .collect::<Vec<Value>>();
+
+        let guilds: Vec<GuildId> = guilds(bot);
+        let voice: Option<(usize, usize)> = match &bot.songbird {
+            Some(songbird) => Some(voice(songbird, &guilds).await),
+            None => None,
+        };
+
+        bots_status.push(json!({
+            "name": bot.name,
+            "state": status.map(|status| status.state.as_str()),
+            "restarts": status.map(|status| status.restarts),
+            "ready": is_ready(bot).await,
+            "shards": shards,
+            "guilds": guilds.len(),
+            "voice_connections": voice.map(|(connections, _)| connections),
+            "queued_tracks": voice.map(|(_, tracks)| tracks),
+        }));
+    }
+
+    let cache: CacheStats = cache::stats();
+    json!({
+        "bots": bots_status,
+        "database": {
+            "backend": SERVERS_BACKEND.get().map(|backend| backend.as_str()),
+            "reachable": database_reachable().await,
+        },
+        "settings_cache": {
+            "guilds": cache.size,
+            "hits": cache.hits,
+            "misses": cache.misses,
+            "hit_rate": cache.hit_rate(),
+        },
+    })
+}
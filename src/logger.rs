@@ -1,68 +1,229 @@
 //! `log` module initializes and configures logger for this application.
 //!
+//! Logger is configured with following variables:
+//! - `LOG_OUTPUTS` - outputs separated by commas, `file` and/or `stdout` (both by default);
+//! - `LOG_FORMAT` - `text` (default) or `json` (one JSON object per line);
+//! - `LOG_FILE` - path of log file, required if `file` output is used;
+//! - `LOG_MAX_SIZE_MB` and `LOG_MAX_FILES` - size that log file is rotated at (10 MB by default)
+//!   and number of rotated files that are kept (5 by default);
+//! - `LOG_MAX_AGE_DAYS` - age after which rotated files are deleted (they are kept by default);
+//! - `LOG_LEVEL` - level of all records (`info` by default);
+//! - `LOG_LEVELS` - levels of modules separated by commas
+//!   (e.g. `warden::bot::music_playing=debug,serenity=warn`).
+//!
+
+pub mod reporter;
 
 pub use log::Level;
 use log::{log, LevelFilter};
 
-use log4rs::append::file::FileAppender;
-use log4rs::config::{Appender, Config, Root};
+use log4rs::append::console::ConsoleAppender;
+use log4rs::append::rolling_file::policy::compound::roll::fixed_window::FixedWindowRoller;
+use log4rs::append::rolling_file::policy::compound::trigger::size::SizeTrigger;
+use log4rs::append::rolling_file::policy::compound::CompoundPolicy;
+use log4rs::append::rolling_file::RollingFileAppender;
+use log4rs::config::{Appender, Config, Logger, Root};
+use log4rs::encode::json::JsonEncoder;
 use log4rs::encode::pattern::PatternEncoder;
+use log4rs::encode::Encode;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime};
+
+/// Whether records are written as JSON lines.
+///
+static JSON: OnceLock<bool> = OnceLock::new();
+
+/// Returns encoder of records in configured format.
+///
+fn encoder() -> Box<dyn Encode> {
+    if *JSON.get().unwrap_or(&false) {
+        Box::new(JsonEncoder::new())
+    } else {
+        Box::new(PatternEncoder::new("{d} {l} {t} - {m}{n}"))
+    }
+}
+
+/// Parses level filter from its name.
+///
+fn parse_level(level: &str) -> LevelFilter {
+    level
+        .trim()
+        .parse::<LevelFilter>()
+        .expect("Log level should be one of off, error, warn, info, debug and trace")
+}
+
+/// Returns value of numeric variable or default one if it is not set.
+///
+fn number(variable: &str, default: u64) -> u64 {
+    dotenv::var(variable).map_or(default, |value| {
+        value
+            .trim()
+            .parse::<u64>()
+            .expect("Log rotation limits should be numbers")
+    })
+}
+
+/// Deletes rotated log files that are older than `LOG_MAX_AGE_DAYS`.
+///
+fn prune(logfile: &Path, max_age: Duration) {
+    let name: String = match logfile.file_name() {
+        Some(name) => format!("{}.", name.to_string_lossy()),
+        None => return,
+    };
+    let directory: &Path = match logfile.parent() {
+        Some(directory) if !directory.as_os_str().is_empty() => directory,
+        _ => Path::new("."),
+    };
+    let entries: std::fs::ReadDir = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let is_rotated: bool = entry.file_name().to_string_lossy().starts_with(&name);
+        let is_expired: bool = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age > max_age);
+        if is_rotated && is_expired {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
 
 /// Initializes and configures logger.
 ///
 pub fn init_logger() {
-    let logfile = FileAppender::builder()
-        .encoder(Box::new(PatternEncoder::new("{d} {l} {t} - {m}{n}")))
-        .build(dotenv::var("LOG_FILE").expect("LOG_FILE should be provided"))
-        .expect("Logfile should be created correctly");
-
-    let config = Config::builder()
-        .appender(Appender::builder().build("logfile", Box::new(logfile)))
-        .build(Root::builder().appender("logfile").build(LevelFilter::Info))
+    let _ = JSON.set(dotenv::var("LOG_FORMAT").is_ok_and(|format| format.trim() == "json"));
+    let outputs: String = dotenv::var("LOG_OUTPUTS").unwrap_or_else(|_| "file,stdout".to_string());
+    let outputs: Vec<&str> = outputs.split(',').map(str::trim).collect::<Vec<&str>>();
+
+    let mut config = Config::builder();
+    let mut root = Root::builder();
+    if outputs.contains(&"file") {
+        let path: PathBuf =
+            PathBuf::from(dotenv::var("LOG_FILE").expect("LOG_FILE should be provided"));
+        let max_files: u32 = number("LOG_MAX_FILES", 5) as u32;
+        let roller: FixedWindowRoller = FixedWindowRoller::builder()
+            .build(&format!("{}.{{}}", path.display()), max_files)
+            .expect("Log file name should be valid pattern");
+        let trigger: SizeTrigger = SizeTrigger::new(number("LOG_MAX_SIZE_MB", 10) * 1024 * 1024);
+        let logfile = RollingFileAppender::builder()
+            .encoder(encoder())
+            .build(
+                &path,
+                Box::new(CompoundPolicy::new(Box::new(trigger), Box::new(roller))),
+            )
+            .expect("Logfile should be created correctly");
+        config = config.appender(Appender::builder().build("logfile", Box::new(logfile)));
+        root = root.appender("logfile");
+
+        if dotenv::var("LOG_MAX_AGE_DAYS").is_ok() {
+            let max_age: Duration =
+                Duration::from_secs(number("LOG_MAX_AGE_DAYS", 0) * 24 * 60 * 60);
+            prune(&path, max_age);
+            let _ = std::thread::spawn(move || loop {
+                std::thread::sleep(Duration::from_secs(60 * 60));
+                prune(&path, max_age);
+            });
+        }
+    }
+    if outputs.contains(&"stdout") {
+        let stdout = ConsoleAppender::builder().encoder(encoder()).build();
+        config = config.appender(Appender::builder().build("stdout", Box::new(stdout)));
+        root = root.appender("stdout");
+    }
+
+    if let Ok(levels) = dotenv::var("LOG_LEVELS") {
+        for level in levels.split(',').filter(|level| !level.trim().is_empty()) {
+            let (module, level): (&str, &str) = level
+                .split_once('=')
+                .expect("Module levels should be given as module=level");
+            config = config.logger(Logger::builder().build(module.trim(), parse_level(level)));
+        }
+    }
+    let level: LevelFilter =
+        dotenv::var("LOG_LEVEL").map_or(LevelFilter::Info, |level| parse_level(&level));
+    let config = config
+        .build(root.build(level))
         .expect("Config should be created correctly");
 
     let _ = log4rs::init_config(config).expect("Logger should be initalized correctly");
 }
 
-/// Logs and prints message in stdout.
+/// Returns module path that corresponds to source file (e.g. `warden::bot::events` for `src/bot/events.rs`).
+///
+fn target(file: &str) -> String {
+    let module: &str = file.trim_start_matches("src/").trim_end_matches(".rs");
+    if module == "main" {
+        "warden".to_string()
+    } else {
+        format!("warden::{}", module.replace('/', "::"))
+    }
+}
+
+/// Logs message with contextual fields (e.g. guild id, user id, command).
+///
+/// Record is attributed to module of caller, so that levels of modules apply to it.
+/// Fields are attached to JSON records as MDC and appended to message in text records.
+///
+#[track_caller]
+pub fn log_with(level: Level, message: &str, fields: &[(&str, &dyn Display)]) {
+    let target: String = target(std::panic::Location::caller().file());
+    if *JSON.get().unwrap_or(&false) {
+        for (key, value) in fields {
+            log_mdc::insert(*key, value.to_string());
+        }
+        log!(target: target.as_str(), level, "{}", message);
+        log_mdc::clear();
+    } else if fields.is_empty() {
+        log!(target: target.as_str(), level, "{}", message);
+    } else {
+        let fields: Vec<String> = fields
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<String>>();
+        log!(target: target.as_str(), level, "{} [{}]", message, fields.join(" "));
+    }
+}
+
+/// Logs message.
 ///  
+#[track_caller]
 pub fn log(level: Level, message: &str) {
-    log!(level, "{}", message);
-    println!("{}", message);
+    log_with(level, message, &[]);
+}
+
+/// Reports embed to discord channel of given category (or to general log channel if category has no channel),
+/// `message` is used as a plain text entry in log file.
+///
+/// Entries are delivered in batches by `reporter`, which falls back to log file
+/// if entry can not be delivered.
+///
+pub fn log_discord_embed(
+    http: &Arc<serenity::http::Http>,
+    guild_id: serenity::model::id::GuildId,
+    category: crate::db::models::LogCategory,
+    message: &str,
+    embed: serenity::builder::CreateEmbed,
+) {
+    reporter::report(http, guild_id, category, message, embed);
 }
 
 /// Logs in discord channel.
 ///
-pub async fn log_discord(
-    http: impl AsRef<serenity::http::Http>,
+pub fn log_discord(
+    http: &Arc<serenity::http::Http>,
     guild_id: serenity::model::id::GuildId,
+    category: crate::db::models::LogCategory,
     message: &str,
 ) {
-    let connection: &sqlx::SqlitePool = crate::db::connections::SERVERS_DB
-        .get()
-        .expect("Connection should be established at this moment");
-
-    let log_channel_id: crate::db::models::ForeignId = sqlx::query_as::<_, crate::db::models::Setting>(
-        "
-        SELECT log_channel_id FROM settings WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = ?)
-    ",
-    )
-    .bind(guild_id.to_string())
-    .fetch_one(connection)
-    .await
-    .expect("Query should be correct")
-    .log_channel_id;
-
-    if let Some(log_channel_id) = log_channel_id.0 {
-        let log_channel_id: serenity::model::id::ChannelId =
-            serenity::model::id::ChannelId(log_channel_id);
-        if let Err(error) = log_channel_id.say(http, message).await {
-            log(
-                log::Level::Info,
-                &format!("An error occured while trying to log in channel: {}", error),
-            );
-        } else {
-            log(log::Level::Info, message);
-        }
-    }
+    let mut embed: serenity::builder::CreateEmbed = serenity::builder::CreateEmbed::default();
+    let _ = embed
+        .description(message)
+        .timestamp(serenity::model::Timestamp::now());
+    log_discord_embed(http, guild_id, category, message, embed);
 }
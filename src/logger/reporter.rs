@@ -0,0 +1,256 @@
+//! `reporter` module implements reporter that delivers log entries to guild log channels.
+//!
+//! Entries are queued and sent in batches: repeated entries are merged into one with counter,
+//! every channel receives limited number of messages per flush to stay within Discord rate limits,
+//! and entries that can not be delivered (no log channel, missing permissions, etc.) are written
+//! to file log instead. Reporter never panics, since it is called from hot paths.
+//!
+
+use crate::{
+    db::{
+        connections::SERVERS_DB,
+        models::{DbId, LogCategory},
+    },
+    logger,
+};
+use serenity::{
+    builder::CreateEmbed,
+    http::Http,
+    model::{
+        channel::Message,
+        id::{ChannelId, GuildId},
+    },
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard, OnceLock, PoisonError,
+    },
+    time::Duration,
+};
+
+/// Interval between two flushes of queued entries.
+///
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Maximal number of embeds in one message (limit of Discord).
+///
+const EMBEDS_PER_MESSAGE: usize = 10;
+
+/// Maximal number of messages that are sent in one channel per flush
+/// (Discord allows 5 messages per 5 seconds in channel).
+///
+const MESSAGES_PER_FLUSH: usize = 5;
+
+/// Maximal number of distinct entries that are queued for guild between two flushes,
+/// entries above it are written to file log only.
+///
+const MAX_PENDING: usize = EMBEDS_PER_MESSAGE * MESSAGES_PER_FLUSH;
+
+/// `Report` struct represents queued log entry.
+///
+struct Report {
+    /// Category of entry.
+    ///
+    category: LogCategory,
+    /// Plain text of entry that is used for deduplication and file log.
+    ///
+    message: String,
+    /// Embed that is sent to log channel.
+    ///
+    embed: CreateEmbed,
+    /// Number of times entry was reported since last flush.
+    ///
+    count: u32,
+}
+
+/// `Pending` struct holds entries that are queued for guild.
+///
+struct Pending {
+    /// Client that is used to send entries.
+    ///
+    http: Arc<Http>,
+    /// Queued entries in order of their first report.
+    ///
+    reports: Vec<Report>,
+}
+
+/// Entries that are waiting to be sent.
+///
+static PENDING: OnceLock<Mutex<HashMap<GuildId, Pending>>> = OnceLock::new();
+
+/// Whether flushing loop was started.
+///
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Locks queued entries, ignoring poisoning, since reporter should never panic.
+///
+fn pending() -> MutexGuard<'static, HashMap<GuildId, Pending>> {
+    PENDING
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Queues entry for guild log channel of given category.
+///
+pub fn report(
+    http: &Arc<Http>,
+    guild_id: GuildId,
+    category: LogCategory,
+    message: &str,
+    embed: CreateEmbed,
+) {
+    if !STARTED.swap(true, Ordering::SeqCst) {
+        let _ = tokio::spawn(async {
+            let mut interval: tokio::time::Interval = tokio::time::interval(FLUSH_INTERVAL);
+            loop {
+                let _ = interval.tick().await;
+                flush().await;
+            }
+        });
+    }
+
+    let mut pending: MutexGuard<'_, HashMap<GuildId, Pending>> = pending();
+    let guild: &mut Pending = pending.entry(guild_id).or_insert_with(|| Pending {
+        http: Arc::clone(http),
+        reports: Vec::new(),
+    });
+    if let Some(report) = guild
+        .reports
+        .iter_mut()
+        .find(|report| report.category == category && report.message == message)
+    {
+        report.count += 1;
+    } else if guild.reports.len() < MAX_PENDING {
+        guild.reports.push(Report {
+            category,
+            message: message.to_string(),
+            embed,
+            count: 1,
+        });
+    } else {
+        logger::log_with(
+            log::Level::Info,
+            message,
+            &[("guild_id", &guild_id), ("category", &category.as_str())],
+        );
+    }
+}
+
+/// Returns log channel of given category in guild (or general log channel if category has none).
+///
+/// Returns `None` if there is no such channel or it can not be found out.
+///
+async fn log_channel(guild_id: GuildId, category: LogCategory) -> Option<ChannelId> {
+    let connection: &sqlx::AnyPool = SERVERS_DB.get()?;
+
+    let (log_channel_id,): (Option<i64>,) = sqlx::query_as(
+        "
+        SELECT COALESCE(
+            (SELECT channel_id FROM log_routes WHERE guild_id = $1 AND category = $2),
+            (SELECT log_channel_id FROM settings WHERE id = (SELECT settings_id FROM guilds WHERE discord_id = $1))
+        )
+    ",
+    )
+    .bind(guild_id.db_id())
+    .bind(category.as_str())
+    .fetch_optional(connection)
+    .await
+    .ok()
+    .flatten()?;
+    Some(ChannelId(u64::try_from(log_channel_id?).ok()?))
+}
+
+/// Writes entry to file log, noting how many times it was repeated.
+///
+fn log_to_file(level: log::Level, guild_id: GuildId, report: &Report, reason: Option<&str>) {
+    let message: String = match report.count {
+        1 => report.message.clone(),
+        count => format!("{} (repeated {} times)", report.message, count),
+    };
+    let message: String = match reason {
+        Some(reason) => format!("{} (not delivered: {})", message, reason),
+        None => message,
+    };
+    logger::log_with(
+        level,
+        &message,
+        &[
+            ("guild_id", &guild_id),
+            ("category", &report.category.as_str()),
+        ],
+    );
+}
+
+/// Sends queued entries of guild, grouping them by log channel.
+///
+/// Log channel of every category is looked up once per flush.
+///
+async fn send(guild_id: GuildId, pending: Pending) {
+    let mut routes: HashMap<LogCategory, Option<ChannelId>> = HashMap::new();
+    let mut channels: Vec<(Option<ChannelId>, Vec<Report>)> = Vec::new();
+    for report in pending.reports {
+        let channel_id: Option<ChannelId> = match routes.get(&report.category) {
+            Some(channel_id) => *channel_id,
+            None => {
+                let channel_id: Option<ChannelId> = log_channel(guild_id, report.category).await;
+                let _ = routes.insert(report.category, channel_id);
+                channel_id
+            }
+        };
+        match channels
+            .iter_mut()
+            .find(|(channel, _)| *channel == channel_id)
+        {
+            Some((_, reports)) => reports.push(report),
+            None => channels.push((channel_id, vec![report])),
+        }
+    }
+
+    for (channel_id, reports) in channels {
+        let channel_id: ChannelId = match channel_id {
+            Some(channel_id) => channel_id,
+            None => {
+                for report in &reports {
+                    log_to_file(log::Level::Info, guild_id, report, None);
+                }
+                continue;
+            }
+        };
+        for chunk in reports.chunks(EMBEDS_PER_MESSAGE) {
+            let embeds: Vec<CreateEmbed> = chunk
+                .iter()
+                .map(|report| {
+                    let mut embed: CreateEmbed = report.embed.clone();
+                    if report.count > 1 {
+                        let _ =
+                            embed.footer(|f| f.text(format!("Repeated {} times", report.count)));
+                    }
+                    embed
+                })
+                .collect::<Vec<CreateEmbed>>();
+            let result: serenity::Result<Message> = channel_id
+                .send_message(&pending.http, |m| m.set_embeds(embeds))
+                .await;
+            for report in chunk {
+                match &result {
+                    Ok(_) => log_to_file(log::Level::Info, guild_id, report, None),
+                    Err(error) => {
+                        log_to_file(log::Level::Warn, guild_id, report, Some(&error.to_string()))
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Sends all queued entries.
+///
+pub async fn flush() {
+    let pending: HashMap<GuildId, Pending> = std::mem::take(&mut *pending());
+    for (guild_id, pending) in pending {
+        send(guild_id, pending).await;
+    }
+}
@@ -11,7 +11,15 @@ mod db;
 use crate::db::init_db;
 
 mod bot;
-use crate::bot::Bot;
+use crate::bot::{Bot, BotHandle};
+
+mod cli;
+use crate::cli::Command;
+
+mod health;
+mod metrics;
+
+use tokio::task::JoinHandle;
 
 const MAIN_BOT_PREFIX: &str = "-";
 const MUSIC_BOT_PREFIXES: [&str; 3] = ["music1 ", "music2 ", "music3 "];
@@ -21,7 +29,31 @@ async fn main() {
     let _ = dotenv::from_filename("SETTINGS.env").ok();
 
     init_logger();
+    let args: Vec<String> = std::env::args().skip(1).collect::<Vec<String>>();
+    let command: Command = match Command::parse(&args) {
+        Ok(command) => command,
+        Err(usage) => {
+            eprintln!("{}", usage);
+            std::process::exit(2);
+        }
+    };
+    match command {
+        Command::Run => run().await,
+        command => {
+            if let Err(error) = cli::execute(command).await {
+                logger::log(log::Level::Error, &error);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Starts all bots and runs them until interruption.
+///
+async fn run() {
     init_db().await;
+    db::backup::start();
+    let _ = bot::owner::STARTED_AT.set(std::time::Instant::now());
 
     let main_token: String = dotenv::var("MAIN_DISCORD_TOKEN").expect("Token should be provided");
     let music1_token: String =
@@ -31,82 +63,61 @@ async fn main() {
     let music3_token: String =
         dotenv::var("MUSIC3_DISCORD_TOKEN").expect("Token should be provided");
 
-    let mut main_bot: Bot = Bot::new(
+    let main_bot: Bot = Bot::new(
+        "main_bot",
         main_token,
         MAIN_BOT_PREFIX,
         vec![
             &bot::GENERAL_GROUP,
             &bot::settings::SETTINGS_GROUP,
+            &bot::moderation::MODERATION_GROUP,
+            &bot::automod::AUTOMOD_GROUP,
+            &bot::raid::RAID_GROUP,
             &bot::music_order::MUSICORDER_GROUP,
+            &bot::backup::BACKUP_GROUP,
+            &bot::owner::OWNER_GROUP,
         ],
         bot::events::Handler,
         false,
-    )
-    .await;
-    let mut music1_bot: Bot = Bot::new(
+    );
+    let music1_bot: Bot = Bot::new(
+        "music1_bot",
         music1_token,
         MUSIC_BOT_PREFIXES[0],
         vec![],
         bot::events::MusicHandler1,
         true,
-    )
-    .await;
-    let mut music2_bot: Bot = Bot::new(
+    );
+    let music2_bot: Bot = Bot::new(
+        "music2_bot",
         music2_token,
         MUSIC_BOT_PREFIXES[1],
         vec![],
         bot::events::MusicHandler2,
         true,
-    )
-    .await;
-    let mut music3_bot: Bot = Bot::new(
+    );
+    let music3_bot: Bot = Bot::new(
+        "music3_bot",
         music3_token,
         MUSIC_BOT_PREFIXES[2],
         vec![],
         bot::events::MusicHandler3,
         true,
-    )
-    .await;
-    tokio::spawn(async move {
-        if let Err(error) = main_bot.run().await {
-            logger::log(
-                log::Level::Error,
-                &format!("An error occurred while running the main_bot: {:?}", error),
-            );
-        };
-    });
-    tokio::spawn(async move {
-        if let Err(error) = music1_bot.run().await {
-            logger::log(
-                log::Level::Error,
-                &format!(
-                    "An error occurred while running the music2_bot: {:?}",
-                    error
-                ),
-            );
-        };
-    });
-    tokio::spawn(async move {
-        if let Err(error) = music2_bot.run().await {
-            logger::log(
-                log::Level::Error,
-                &format!(
-                    "An error occurred while running the music2_bot: {:?}",
-                    error
-                ),
-            );
-        };
-    });
-    tokio::spawn(async move {
-        if let Err(error) = music3_bot.run().await {
-            logger::log(
-                log::Level::Error,
-                &format!(
-                    "An error occurred while running the music3_bot: {:?}",
-                    error
-                ),
-            );
-        };
-    });
-    let _ = tokio::signal::ctrl_c().await;
+    );
+    let handles: Vec<BotHandle> = vec![
+        main_bot.handle(),
+        music1_bot.handle(),
+        music2_bot.handle(),
+        music3_bot.handle(),
+    ];
+    let tasks: Vec<JoinHandle<()>> = vec![
+        tokio::spawn(bot::supervisor::supervise(main_bot)),
+        tokio::spawn(bot::supervisor::supervise(music1_bot)),
+        tokio::spawn(bot::supervisor::supervise(music2_bot)),
+        tokio::spawn(bot::supervisor::supervise(music3_bot)),
+    ];
+    health::start(handles.clone()).await;
+
+    bot::shutdown::wait_for_signal().await;
+    bot::shutdown::shutdown(handles, tasks).await;
 }
@@ -0,0 +1,334 @@
+//! `metrics` module collects metrics of bots and renders them in Prometheus text format.
+//!
+//! Metrics are exposed on `/metrics` path of health server (see `health` module).
+//!
+
+use crate::db::cache::{self, CacheStats};
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    future::Future,
+    sync::{Mutex, MutexGuard, OnceLock},
+    time::{Duration, Instant},
+};
+
+/// Upper bounds of histogram buckets in seconds.
+///
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// `Counter` enum represents counters that are collected.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Counter {
+    /// Invocations of commands, labelled by command.
+    ///
+    CommandInvocations,
+    /// Invocations of commands that returned error, labelled by command.
+    ///
+    CommandFailures,
+    /// Members that reached stage of registration, labelled by stage.
+    ///
+    Registrations,
+}
+impl Counter {
+    /// Returns name, help and label name of counter.
+    ///
+    fn describe(&self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Counter::CommandInvocations => (
+                "warden_command_invocations_total",
+                "Number of invoked commands.",
+                "command",
+            ),
+            Counter::CommandFailures => (
+                "warden_command_failures_total",
+                "Number of commands that failed.",
+                "command",
+            ),
+            Counter::Registrations => (
+                "warden_registration_funnel_total",
+                "Number of members that reached stage of registration.",
+                "stage",
+            ),
+        }
+    }
+}
+
+/// `Histogram` enum represents durations that are collected.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Histogram {
+    /// Time that event handlers take, labelled by event.
+    ///
+    EventDuration,
+    /// Time that database methods take, labelled by method.
+    ///
+    QueryDuration,
+}
+impl Histogram {
+    /// Returns name, help and label name of histogram.
+    ///
+    fn describe(&self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Histogram::EventDuration => (
+                "warden_event_handler_duration_seconds",
+                "Time that event handlers take.",
+                "event",
+            ),
+            Histogram::QueryDuration => (
+                "warden_sql_query_duration_seconds",
+                "Time that database methods take.",
+                "method",
+            ),
+        }
+    }
+}
+
+/// `Observations` struct holds observed durations of single histogram series.
+///
+#[derive(Clone, Debug, Default)]
+struct Observations {
+    /// Number of observations that fall into every bucket.
+    ///
+    buckets: [u64; BUCKETS.len()],
+    /// Sum of observed durations in seconds.
+    ///
+    sum: f64,
+    /// Number of observations.
+    ///
+    count: u64,
+}
+
+/// Values of counters.
+///
+static COUNTERS: OnceLock<Mutex<BTreeMap<(Counter, String), u64>>> = OnceLock::new();
+
+/// Observations of histograms.
+///
+static HISTOGRAMS: OnceLock<Mutex<BTreeMap<(Histogram, String), Observations>>> = OnceLock::new();
+
+/// Increments counter with given label.
+///
+pub fn increment(counter: Counter, label: &str) {
+    *COUNTERS
+        .get_or_init(|| Mutex::new(BTreeMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned")
+        .entry((counter, label.to_string()))
+        .or_insert(0) += 1;
+}
+
+/// Records duration in histogram with given label.
+///
+pub fn observe(histogram: Histogram, label: &str, duration: Duration) {
+    let seconds: f64 = duration.as_secs_f64();
+    let mut histograms: MutexGuard<'_, BTreeMap<(Histogram, String), Observations>> = HISTOGRAMS
+        .get_or_init(|| Mutex::new(BTreeMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned");
+    let observations: &mut Observations = histograms
+        .entry((histogram, label.to_string()))
+        .or_default();
+    for (bucket, bound) in observations.buckets.iter_mut().zip(BUCKETS) {
+        if seconds <= bound {
+            *bucket += 1;
+        }
+    }
+    observations.sum += seconds;
+    observations.count += 1;
+}
+
+/// `Timer` struct records time that passed since its creation in histogram when it is dropped.
+///
+pub struct Timer {
+    /// Histogram that duration is recorded in.
+    ///
+    histogram: Histogram,
+    /// Label of duration.
+    ///
+    label: &'static str,
+    /// Moment when timer was started.
+    ///
+    started: Instant,
+}
+impl Timer {
+    /// Starts timer of event handler.
+    ///
+    pub fn event(event: &'static str) -> Timer {
+        Timer {
+            histogram: Histogram::EventDuration,
+            label: event,
+            started: Instant::now(),
+        }
+    }
+
+    /// Starts timer of database method.
+    ///
+    pub fn query(method: &'static str) -> Timer {
+        Timer {
+            histogram: Histogram::QueryDuration,
+            label: method,
+            started: Instant::now(),
+        }
+    }
+}
+impl Drop for Timer {
+    fn drop(&mut self) {
+        observe(self.histogram, self.label, self.started.elapsed());
+    }
+}
+
+/// Awaits database query and records its duration under given method name.
+///
+/// Functions that only talk to database start [`Timer::query`] themselves instead.
+///
+pub async fn timed<F: Future>(method: &'static str, query: F) -> F::Output {
+    let _timer: Timer = Timer::query(method);
+    query.await
+}
+
+/// Counts invocation of command and its failure.
+///
+pub fn record_command(command_name: &str, failed: bool) {
+    increment(Counter::CommandInvocations, command_name);
+    if failed {
+        increment(Counter::CommandFailures, command_name);
+    }
+}
+
+/// Escapes label value as Prometheus text format requires.
+///
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Writes `HELP` and `TYPE` lines of metric.
+///
+fn header(output: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(output, "# HELP {} {}", name, help);
+    let _ = writeln!(output, "# TYPE {} {}", name, kind);
+}
+
+/// Renders gauge with given samples, which are pairs of label values and values.
+///
+pub fn render_gauge(
+    output: &mut String,
+    name: &str,
+    help: &str,
+    label: &str,
+    samples: &[(String, f64)],
+) {
+    header(output, name, help, "gauge");
+    for (value, sample) in samples {
+        let _ = writeln!(
+            output,
+            "{}{{{}=\"{}\"}} {}",
+            name,
+            label,
+            escape(value),
+            sample
+        );
+    }
+}
+
+/// Renders all collected metrics and statistics of settings cache.
+///
+pub fn render() -> String {
+    let mut output: String = String::new();
+
+    let counters: BTreeMap<(Counter, String), u64> = COUNTERS
+        .get_or_init(|| Mutex::new(BTreeMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned")
+        .clone();
+    for counter in [
+        Counter::CommandInvocations,
+        Counter::CommandFailures,
+        Counter::Registrations,
+    ] {
+        let (name, help, label): (&str, &str, &str) = counter.describe();
+        header(&mut output, name, help, "counter");
+        for ((_, value), count) in counters.iter().filter(|((kind, _), _)| *kind == counter) {
+            let _ = writeln!(
+                output,
+                "{}{{{}=\"{}\"}} {}",
+                name,
+                label,
+                escape(value),
+                count
+            );
+        }
+    }
+
+    let histograms: BTreeMap<(Histogram, String), Observations> = HISTOGRAMS
+        .get_or_init(|| Mutex::new(BTreeMap::new()))
+        .lock()
+        .expect("Lock should not be poisoned")
+        .clone();
+    for histogram in [Histogram::EventDuration, Histogram::QueryDuration] {
+        let (name, help, label): (&str, &str, &str) = histogram.describe();
+        header(&mut output, name, help, "histogram");
+        for ((_, value), observations) in histograms
+            .iter()
+            .filter(|((kind, _), _)| *kind == histogram)
+        {
+            let value: String = escape(value);
+            for (bucket, bound) in observations.buckets.iter().zip(BUCKETS) {
+                let _ = writeln!(
+                    output,
+                    "{}_bucket{{{}=\"{}\",le=\"{}\"}} {}",
+                    name, label, value, bound, bucket
+                );
+            }
+            let _ = writeln!(
+                output,
+                "{}_bucket{{{}=\"{}\",le=\"+Inf\"}} {}",
+                name, label, value, observations.count
+            );
+            let _ = writeln!(
+                output,
+                "{}_sum{{{}=\"{}\"}} {}",
+                name, label, value, observations.sum
+            );
+            let _ = writeln!(
+                output,
+                "{}_count{{{}=\"{}\"}} {}",
+                name, label, value, observations.count
+            );
+        }
+    }
+
+    let stats: CacheStats = cache::stats();
+    header(
+        &mut output,
+        "warden_settings_cache_hits_total",
+        "Number of settings lookups answered from cache.",
+        "counter",
+    );
+    let _ = writeln!(output, "warden_settings_cache_hits_total {}", stats.hits);
+    header(
+        &mut output,
+        "warden_settings_cache_misses_total",
+        "Number of settings lookups that needed database query.",
+        "counter",
+    );
+    let _ = writeln!(
+        output,
+        "warden_settings_cache_misses_total {}",
+        stats.misses
+    );
+    header(
+        &mut output,
+        "warden_settings_cache_guilds",
+        "Number of guilds whose settings are cached.",
+        "gauge",
+    );
+    let _ = writeln!(output, "warden_settings_cache_guilds {}", stats.size);
+
+    output
+}